@@ -1,37 +1,72 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    from_json, to_json_binary, Addr, Binary, CosmosMsg, Deps, DepsMut, Empty, Env, MessageInfo,
-    Order, Reply, Response, StdResult, SubMsg, Uint128, Uint256, WasmMsg,
+    coins, from_json, to_json_binary, Addr, BankMsg, Binary, CosmosMsg, Deps, DepsMut, Empty, Env,
+    MessageInfo, Order, OverflowError, OverflowOperation, Reply, Response, StdError, StdResult,
+    Storage, SubMsg, Timestamp, Uint128, Uint256, WasmMsg,
 };
-use cw2::set_contract_version;
-use cw20::{Cw20Contract, Cw20ExecuteMsg, Cw20ReceiveMsg};
+use cw2::{get_contract_version, set_contract_version};
+use cw20::{Cw20Contract, Cw20ExecuteMsg, Cw20ReceiveMsg, Denom};
 use cw721::{Cw721ExecuteMsg, Cw721ReceiveMsg};
+use cw_storage_plus::{Bound, Map};
+use cw_utils::{must_pay, one_coin, Expiration};
 
 use crate::error::ContractError;
-use crate::msg::{ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg, ReceiveMsg, ReceiveNftMsg};
-use crate::state::{Config, Listing, CONFIG, LISTINGS, LISTING_COUNTER};
+use crate::msg::{
+    AuctionResponse, CheckRoyaltiesResponse, CollectionStatsResponse, CollectionsResponse,
+    ConfigResponse, Cw2981QueryMsg, ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg, ReceiveMsg,
+    ReceiveNftMsg, RoyaltiesInfoResponse, SortOrder,
+};
+use crate::state::{
+    denom_key, listings, offers, sales, Auction, AuctionKind, Bid, Config, Listing, Offer,
+    RoyaltyTotal, Sale, Volume, ACCEPTED_CW20S, ACCEPTED_NATIVES, AUCTIONS, AUCTION_COUNTER,
+    COLLECTIONS, COLLECTION_STATS, CONFIG, LISTING_COUNTER, OFFER_COUNTER, ROYALTIES_PAID,
+    SALE_COUNTER, VOLUME,
+};
+use semver::Version;
 
 pub const CONTRACT_NAME: &str = "cw404-marketplace";
-pub const CONTRACT_VERSION: &str = "0.1.0";
+pub const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
 pub const LISTING_REPLY: u64 = 1;
 
+// 10%
+const MAX_FEE_BPS: u16 = 1_000;
+
+// expired listings returned by one cleanup call
+const MAX_CLEANUP_LIMIT: u32 = 30;
+
+// legacy listings re-keyed by one migration unless it asks otherwise
+const MIGRATE_LIMIT: u32 = 100;
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
     deps: DepsMut,
     _env: Env,
-    _info: MessageInfo,
+    info: MessageInfo,
     msg: InstantiateMsg,
 ) -> Result<Response, ContractError> {
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    cw_ownable::initialize_owner(deps.storage, deps.api, Some(info.sender.as_str()))?;
 
     let config = Config {
         cw404_address: deps.api.addr_validate(&msg.cw404_address)?,
         cw20_address: deps.api.addr_validate(&msg.cw20_address)?,
+        fee_bps: msg.fee_bps,
+        fee_collector: msg
+            .fee_collector
+            .map(|addr| deps.api.addr_validate(&addr))
+            .transpose()?,
+        open: false,
     };
+    validate_fee(&config)?;
 
     CONFIG.save(deps.storage, &config)?;
+    ACCEPTED_CW20S.save(deps.storage, config.cw20_address.clone(), &Empty {})?;
+    COLLECTIONS.save(deps.storage, config.cw404_address.clone(), &Empty {})?;
+    for denom in msg.accepted_natives.unwrap_or_default() {
+        ACCEPTED_NATIVES.save(deps.storage, denom, &Empty {})?;
+    }
     LISTING_COUNTER.save(deps.storage, &0u128)?;
 
     Ok(Response::new()
@@ -48,27 +83,215 @@ pub fn execute(
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
     match msg {
-        ExecuteMsg::CancelListing { id } => execute_cancel_listing(deps, info, id),
-        ExecuteMsg::Receive(msg) => execute_receive(deps, info, msg),
+        ExecuteMsg::CancelListing { collection, id } => {
+            execute_cancel_listing(deps, info, collection, id)
+        }
+        ExecuteMsg::Buy { collection, id } => execute_buy(deps, env, info, collection, id),
+        ExecuteMsg::UpdatePrice {
+            collection,
+            id,
+            price,
+        } => execute_update_price(deps, env, info, collection, id, price),
+        ExecuteMsg::CleanupExpired { limit } => execute_cleanup_expired(deps, env, limit),
+        ExecuteMsg::Receive(msg) => execute_receive(deps, env, info, msg),
         ExecuteMsg::ReceiveNft(msg) => execute_receive_nft(deps, env, info, msg),
+        ExecuteMsg::MakeOffer {
+            collection,
+            nft_id,
+            price,
+            expires,
+        } => {
+            let payment = one_coin(&info)?;
+            assert_native(deps.as_ref(), &payment.denom)?;
+            if payment.amount != price {
+                return Err(ContractError::IncorrectPayment {
+                    price: Uint256::from_uint128(price),
+                });
+            }
+            make_offer(
+                deps,
+                env,
+                info.sender,
+                collection,
+                nft_id,
+                price,
+                Denom::Native(payment.denom),
+                expires,
+            )
+        }
+        ExecuteMsg::CancelOffer { id } => execute_cancel_offer(deps, env, info, id),
+        ExecuteMsg::PlaceBid { id } => {
+            let auction = AUCTIONS.load(deps.storage, id)?;
+            let denom = match auction.denom {
+                Denom::Native(denom) => denom,
+                Denom::Cw20(_) => return Err(ContractError::IncorrectDenom {}),
+            };
+            let amount = must_pay(&info, &denom)?;
+            receive_bid(deps, env, id, info.sender, amount, Denom::Native(denom))
+        }
+        ExecuteMsg::SettleAuction { id } => execute_settle_auction(deps, env, id),
+        ExecuteMsg::CancelAuction { id } => execute_cancel_auction(deps, info, id),
+        ExecuteMsg::BuyMany { ids, max_total } => {
+            let payment = one_coin(&info)?;
+            let candidates = pick_listings(
+                deps.as_ref(),
+                &env,
+                ids,
+                &Denom::Native(payment.denom.clone()),
+                payment.amount.min(max_total),
+            )?;
+            receive_buy_many(
+                deps,
+                env,
+                info.sender,
+                candidates,
+                payment.amount,
+                Denom::Native(payment.denom),
+            )
+        }
+        ExecuteMsg::SweepFloor {
+            collection,
+            count,
+            max_price_each,
+        } => {
+            let payment = one_coin(&info)?;
+            let candidates = pick_floor(
+                deps.as_ref(),
+                &env,
+                collection,
+                count,
+                max_price_each,
+                &Denom::Native(payment.denom.clone()),
+                payment.amount,
+            )?;
+            receive_buy_many(
+                deps,
+                env,
+                info.sender,
+                candidates,
+                payment.amount,
+                Denom::Native(payment.denom),
+            )
+        }
+        ExecuteMsg::UpdateConfig {
+            fee_bps,
+            fee_collector,
+            add_cw20s,
+            remove_cw20s,
+            add_natives,
+            remove_natives,
+            open,
+            add_collections,
+            remove_collections,
+        } => execute_update_config(
+            deps,
+            info,
+            fee_bps,
+            fee_collector,
+            add_cw20s,
+            remove_cw20s,
+            add_natives,
+            remove_natives,
+            open,
+            add_collections,
+            remove_collections,
+        ),
+        ExecuteMsg::UpdateOwnership(action) => {
+            let ownership = cw_ownable::update_ownership(deps, &env.block, &info.sender, action)?;
+            Ok(Response::new()
+                .add_attribute("action", "update_ownership")
+                .add_attributes(ownership.into_attributes()))
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn execute_update_config(
+    deps: DepsMut,
+    info: MessageInfo,
+    fee_bps: Option<u16>,
+    fee_collector: Option<String>,
+    add_cw20s: Option<Vec<String>>,
+    remove_cw20s: Option<Vec<String>>,
+    add_natives: Option<Vec<String>>,
+    remove_natives: Option<Vec<String>>,
+    open: Option<bool>,
+    add_collections: Option<Vec<String>>,
+    remove_collections: Option<Vec<String>>,
+) -> Result<Response, ContractError> {
+    cw_ownable::assert_owner(deps.storage, &info.sender)?;
+
+    let mut config = CONFIG.load(deps.storage)?;
+    if let Some(fee_bps) = fee_bps {
+        config.fee_bps = fee_bps;
+    }
+    if let Some(fee_collector) = fee_collector {
+        config.fee_collector = Some(deps.api.addr_validate(&fee_collector)?);
+    }
+    if let Some(open) = open {
+        config.open = open;
+    }
+    validate_fee(&config)?;
+    CONFIG.save(deps.storage, &config)?;
+
+    for cw20 in add_cw20s.unwrap_or_default() {
+        ACCEPTED_CW20S.save(deps.storage, deps.api.addr_validate(&cw20)?, &Empty {})?;
+    }
+    // listings already priced in a removed token can still be bought
+    for cw20 in remove_cw20s.unwrap_or_default() {
+        ACCEPTED_CW20S.remove(deps.storage, deps.api.addr_validate(&cw20)?);
+    }
+    for denom in add_natives.unwrap_or_default() {
+        ACCEPTED_NATIVES.save(deps.storage, denom, &Empty {})?;
+    }
+    for denom in remove_natives.unwrap_or_default() {
+        ACCEPTED_NATIVES.remove(deps.storage, denom);
+    }
+    for collection in add_collections.unwrap_or_default() {
+        COLLECTIONS.save(
+            deps.storage,
+            deps.api.addr_validate(&collection)?,
+            &Empty {},
+        )?;
     }
+    // existing listings of a removed collection can still be bought or cancelled
+    for collection in remove_collections.unwrap_or_default() {
+        COLLECTIONS.remove(deps.storage, deps.api.addr_validate(&collection)?);
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", "update_config")
+        .add_attribute("fee_bps", config.fee_bps.to_string()))
+}
+
+fn validate_fee(config: &Config) -> Result<(), ContractError> {
+    if config.fee_bps > MAX_FEE_BPS {
+        return Err(ContractError::FeeTooHigh {
+            bps: config.fee_bps,
+            max: MAX_FEE_BPS,
+        });
+    }
+    if config.fee_bps > 0 && config.fee_collector.is_none() {
+        return Err(ContractError::MissingFeeCollector {});
+    }
+    Ok(())
 }
 
 pub fn execute_cancel_listing(
     deps: DepsMut,
     info: MessageInfo,
+    collection: String,
     id: String,
 ) -> Result<Response, ContractError> {
-    let listing = LISTINGS.load(deps.storage, id.clone())?;
+    let key = listing_key(deps.as_ref(), &collection, id)?;
+    let listing = listings().load(deps.storage, key.clone())?;
 
     if listing.owner != info.sender {
         return Err(ContractError::Unauthorized {});
     }
 
-    let config = CONFIG.load(deps.storage)?;
-
     let msg = CosmosMsg::Wasm(WasmMsg::Execute {
-        contract_addr: config.cw404_address.to_string(),
+        contract_addr: listing.collection.to_string(),
         msg: to_json_binary(&Cw721ExecuteMsg::TransferNft {
             recipient: listing.owner.to_string(),
             token_id: listing.nft_id.clone(),
@@ -76,7 +299,7 @@ pub fn execute_cancel_listing(
         funds: vec![],
     });
 
-    LISTINGS.remove(deps.storage, id.clone());
+    listings().remove(deps.storage, key)?;
 
     let _ = LISTING_COUNTER.update(deps.storage, |counter: u128| -> StdResult<u128> {
         Ok(counter.checked_sub(1u128).unwrap())
@@ -84,10 +307,89 @@ pub fn execute_cancel_listing(
 
     Ok(Response::new()
         .add_attribute("action", "cancel listing")
+        .add_attribute("collection", listing.collection)
         .add_attribute("NFT", listing.nft_id)
         .add_message(msg))
 }
 
+pub fn execute_update_price(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    collection: String,
+    id: String,
+    price: Uint256,
+) -> Result<Response, ContractError> {
+    let key = listing_key(deps.as_ref(), &collection, id)?;
+    let mut listing = listings().load(deps.storage, key.clone())?;
+
+    if listing.owner != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+    if is_expired(&listing, &env) {
+        return Err(ContractError::ListingExpired { id: listing.nft_id });
+    }
+
+    listing.price = price;
+    listings().save(deps.storage, key, &listing)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "update price")
+        .add_attribute("NFT", listing.nft_id)
+        .add_attribute("price", price))
+}
+
+pub fn execute_cleanup_expired(
+    deps: DepsMut,
+    env: Env,
+    limit: Option<u32>,
+) -> Result<Response, ContractError> {
+    let limit = limit.unwrap_or(10).min(MAX_CLEANUP_LIMIT);
+
+    // everything expiring up to now sorts before the lowest key of the next
+    // nanosecond
+    let end = Bound::exclusive((
+        env.block.time.nanos() + 1,
+        (Addr::unchecked(""), String::new()),
+    ));
+    let expired = listings()
+        .idx
+        .expires_at
+        .range(deps.storage, None, Some(end), Order::Ascending)
+        .take(limit as usize)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    let count = expired.len() as u128;
+    let mut res = Response::new()
+        .add_attribute("action", "cleanup expired")
+        .add_attribute("count", count.to_string());
+    for (key, listing) in expired {
+        listings().remove(deps.storage, key)?;
+        res = res.add_message(transfer_nft_msg(
+            &listing.collection,
+            &listing.owner,
+            &listing.nft_id,
+        )?);
+    }
+    LISTING_COUNTER.update(deps.storage, |counter| -> StdResult<u128> {
+        counter.checked_sub(count).ok_or_else(|| {
+            StdError::overflow(OverflowError::new(OverflowOperation::Sub, counter, count))
+        })
+    })?;
+
+    Ok(res)
+}
+
+fn listing_key(deps: Deps, collection: &str, id: String) -> StdResult<(Addr, String)> {
+    Ok((deps.api.addr_validate(collection)?, id))
+}
+
+fn is_expired(listing: &Listing, env: &Env) -> bool {
+    listing
+        .expires_at
+        .is_some_and(|expires_at| env.block.time >= expires_at)
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn reply(_deps: DepsMut, _env: Env, reply: Reply) -> Result<Response, ContractError> {
     match reply.id {
@@ -96,241 +398,1589 @@ pub fn reply(_deps: DepsMut, _env: Env, reply: Reply) -> Result<Response, Contra
     }
 }
 
+pub fn execute_buy(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    collection: String,
+    id: String,
+) -> Result<Response, ContractError> {
+    let listing = listings().load(deps.storage, listing_key(deps.as_ref(), &collection, id)?)?;
+    let denom = match &listing.denom {
+        Denom::Native(denom) => denom.clone(),
+        Denom::Cw20(_) => return Err(ContractError::IncorrectDenom {}),
+    };
+    let amount = must_pay(&info, &denom)?;
+
+    receive_buy(
+        deps,
+        env,
+        listing.collection,
+        listing.nft_id,
+        info.sender.into_string(),
+        amount,
+        Denom::Native(denom),
+    )
+}
+
 pub fn execute_receive(
     deps: DepsMut,
+    env: Env,
     info: MessageInfo,
     cw20_receive_msg: Cw20ReceiveMsg,
 ) -> Result<Response, ContractError> {
-    let config = CONFIG.load(deps.storage)?;
-    if config.cw20_address != info.sender {
+    if !ACCEPTED_CW20S.has(deps.storage, info.sender.clone()) {
         return Err(ContractError::Unauthorized {});
     }
 
     let msg: ReceiveMsg = from_json(&cw20_receive_msg.msg)?;
     match msg {
-        ReceiveMsg::Buy { id } => receive_buy(
-            deps,
-            id,
-            cw20_receive_msg.sender,
-            cw20_receive_msg.amount,
-            info.sender,
-        ),
+        ReceiveMsg::Buy { collection, id } => {
+            let collection = deps.api.addr_validate(&collection)?;
+            receive_buy(
+                deps,
+                env,
+                collection,
+                id,
+                cw20_receive_msg.sender,
+                cw20_receive_msg.amount,
+                Denom::Cw20(info.sender),
+            )
+        }
+        ReceiveMsg::MakeOffer {
+            collection,
+            nft_id,
+            price,
+            expires,
+        } => {
+            if cw20_receive_msg.amount != price {
+                return Err(ContractError::IncorrectPayment {
+                    price: Uint256::from_uint128(price),
+                });
+            }
+            let bidder = deps.api.addr_validate(&cw20_receive_msg.sender)?;
+            make_offer(
+                deps,
+                env,
+                bidder,
+                collection,
+                nft_id,
+                price,
+                Denom::Cw20(info.sender),
+                expires,
+            )
+        }
+        ReceiveMsg::BuyMany { ids, max_total } => {
+            let buyer = deps.api.addr_validate(&cw20_receive_msg.sender)?;
+            let denom = Denom::Cw20(info.sender);
+            let candidates = pick_listings(
+                deps.as_ref(),
+                &env,
+                ids,
+                &denom,
+                cw20_receive_msg.amount.min(max_total),
+            )?;
+            receive_buy_many(deps, env, buyer, candidates, cw20_receive_msg.amount, denom)
+        }
+        ReceiveMsg::SweepFloor {
+            collection,
+            count,
+            max_price_each,
+        } => {
+            let buyer = deps.api.addr_validate(&cw20_receive_msg.sender)?;
+            let denom = Denom::Cw20(info.sender);
+            let candidates = pick_floor(
+                deps.as_ref(),
+                &env,
+                collection,
+                count,
+                max_price_each,
+                &denom,
+                cw20_receive_msg.amount,
+            )?;
+            receive_buy_many(deps, env, buyer, candidates, cw20_receive_msg.amount, denom)
+        }
+        ReceiveMsg::PlaceBid { id } => {
+            let bidder = deps.api.addr_validate(&cw20_receive_msg.sender)?;
+            receive_bid(
+                deps,
+                env,
+                id,
+                bidder,
+                cw20_receive_msg.amount,
+                Denom::Cw20(info.sender),
+            )
+        }
     }
 }
 
+/// Stores an offer whose funds the marketplace already holds.
+#[allow(clippy::too_many_arguments)]
+pub fn make_offer(
+    deps: DepsMut,
+    env: Env,
+    bidder: Addr,
+    collection: String,
+    nft_id: Option<String>,
+    price: Uint128,
+    denom: Denom,
+    expires: Option<Expiration>,
+) -> Result<Response, ContractError> {
+    let collection = deps.api.addr_validate(&collection)?;
+    assert_collection(deps.as_ref(), &collection)?;
+    let expires = expires.unwrap_or_default();
+    if expires.is_expired(&env.block) {
+        return Err(ContractError::InvalidExpiration {});
+    }
+
+    let id = OFFER_COUNTER.may_load(deps.storage)?.unwrap_or_default() + 1;
+    OFFER_COUNTER.save(deps.storage, &id)?;
+
+    let offer = Offer {
+        id,
+        bidder,
+        collection,
+        nft_id,
+        price,
+        denom,
+        expires,
+    };
+    offers().save(deps.storage, id, &offer)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "make_offer")
+        .add_attribute("offer_id", id.to_string())
+        .add_attribute("bidder", offer.bidder)
+        .add_attribute("collection", offer.collection)
+        .add_attribute("NFT", offer.nft_id.unwrap_or_else(|| "*".to_string()))
+        .add_attribute("price", offer.price))
+}
+
+pub fn execute_cancel_offer(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    id: u64,
+) -> Result<Response, ContractError> {
+    let offer = offers().load(deps.storage, id)?;
+    if offer.bidder != info.sender && !offer.expires.is_expired(&env.block) {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    offers().remove(deps.storage, id)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "cancel_offer")
+        .add_attribute("offer_id", id.to_string())
+        .add_message(payment_msg(&offer.denom, &offer.bidder, offer.price)?))
+}
+
+/// Sells `token_id`, just received from `seller`, to the bidder of offer
+/// `id` and pays the seller out of the escrow.
+pub fn receive_accept_offer(
+    mut deps: DepsMut,
+    env: Env,
+    collection: Addr,
+    seller: String,
+    token_id: String,
+    id: u64,
+) -> Result<Response, ContractError> {
+    let offer = offers().load(deps.storage, id)?;
+    if offer.expires.is_expired(&env.block) {
+        return Err(ContractError::OfferExpired { id });
+    }
+    if offer.collection != collection
+        || offer
+            .nft_id
+            .as_ref()
+            .is_some_and(|nft_id| *nft_id != token_id)
+    {
+        return Err(ContractError::OfferTokenMismatch { id });
+    }
+    let seller = deps.api.addr_validate(&seller)?;
+
+    offers().remove(deps.storage, id)?;
+
+    let mut res = Response::new()
+        .add_attribute("action", "accept_offer")
+        .add_attribute("offer_id", id.to_string())
+        .add_attribute("NFT", token_id.clone())
+        .add_attribute("seller", seller.clone())
+        .add_attribute("buyer", offer.bidder.clone())
+        .add_message(transfer_nft_msg(&collection, &offer.bidder, &token_id)?);
+
+    res = distribute_sale(
+        deps.branch(),
+        &env,
+        res,
+        Trade {
+            collection,
+            token_id,
+            seller,
+            buyer: offer.bidder,
+            denom: offer.denom,
+            price: offer.price,
+        },
+    )?;
+
+    Ok(res)
+}
+
+/// Settles a purchase paid with `amount` of `denom`, which must match the
+/// listing exactly.
 pub fn receive_buy(
     deps: DepsMut,
+    env: Env,
+    collection: Addr,
     id: String,
     sender: String,
     amount: Uint128,
-    cw20_address: Addr,
+    denom: Denom,
 ) -> Result<Response, ContractError> {
-    let listing = LISTINGS.load(deps.storage, id.clone())?;
+    let listing = listings().load(deps.storage, (collection, id.clone()))?;
 
+    if is_expired(&listing, &env) {
+        return Err(ContractError::ListingExpired { id });
+    }
+    if denom != listing.denom {
+        return Err(ContractError::IncorrectDenom {});
+    }
     if Uint256::from_uint128(amount) != listing.price {
         return Err(ContractError::IncorrectPayment {
             price: listing.price,
         });
     }
 
-    let config = CONFIG.load(deps.storage)?;
-
-    let submsg = SubMsg::reply_on_success(
-        WasmMsg::Execute {
-            contract_addr: config.cw404_address.to_string(),
-            msg: to_json_binary(&Cw721ExecuteMsg::TransferNft {
-                recipient: sender.clone(),
-                token_id: listing.nft_id.clone(),
-            })?,
-            funds: vec![],
-        },
-        LISTING_REPLY,
-    );
-
-    let mut res = Response::new()
+    let res = Response::new()
         .add_attribute("action", "receive_buy")
-        .add_attribute("NFT", listing.nft_id)
+        .add_attribute("collection", listing.collection.clone())
+        .add_attribute("NFT", listing.nft_id.clone())
         .add_attribute("seller", listing.owner.clone().into_string())
-        .add_attribute("buyer", sender)
-        .add_submessage(submsg);
+        .add_attribute("buyer", sender.clone());
 
-    let cw20 = Cw20Contract(cw20_address);
+    let buyer = deps.api.addr_validate(&sender)?;
+    settle_listing(deps, &env, res, listing, buyer, amount)
+}
 
-    let payment = cw20.call(Cw20ExecuteMsg::Transfer {
-        recipient: listing.owner.into_string().clone(),
-        amount,
-    })?;
+/// Hands a paid for listing to `buyer` and pays `price` out to the seller.
+fn settle_listing(
+    mut deps: DepsMut,
+    env: &Env,
+    res: Response,
+    listing: Listing,
+    buyer: Addr,
+    price: Uint128,
+) -> Result<Response, ContractError> {
+    let submsg = SubMsg::reply_on_success(
+        transfer_nft_msg(&listing.collection, &buyer, &listing.nft_id)?,
+        LISTING_REPLY,
+    );
 
-    LISTINGS.remove(deps.storage, id);
+    listings().remove(
+        deps.storage,
+        (listing.collection.clone(), listing.nft_id.clone()),
+    )?;
     let _ = LISTING_COUNTER.update(deps.storage, |counter: u128| -> StdResult<u128> {
         Ok(counter.checked_sub(1u128).unwrap())
     });
-    res = res.add_message(payment);
 
-    Ok(res)
+    distribute_sale(
+        deps.branch(),
+        env,
+        res.add_submessage(submsg),
+        Trade {
+            collection: listing.collection,
+            token_id: listing.nft_id,
+            seller: listing.owner,
+            buyer,
+            denom: listing.denom,
+            price,
+        },
+    )
 }
 
-pub fn execute_receive_nft(
-    deps: DepsMut,
-    _env: Env,
-    info: MessageInfo,
-    receive_msg: Cw721ReceiveMsg,
-) -> Result<Response, ContractError> {
-    let config = CONFIG.load(deps.storage)?;
-    if config.cw404_address != info.sender {
-        return Err(ContractError::Unauthorized {});
-    };
+/// The price of `listing` if it can be bought now with `denom`.
+fn buyable_price(listing: &Listing, env: &Env, denom: &Denom) -> Option<Uint128> {
+    if is_expired(listing, env) || listing.denom != *denom {
+        return None;
+    }
+    Uint128::try_from(listing.price).ok()
+}
 
-    // info.sender is the NFT contract Address
-    let sender = receive_msg.sender.clone();
+/// The listings among `ids` that `budget` buys, taken in order and skipping
+/// any that are gone, expired, priced in another denom or too expensive.
+fn pick_listings(
+    deps: Deps,
+    env: &Env,
+    ids: Vec<(String, String)>,
+    denom: &Denom,
+    mut budget: Uint128,
+) -> StdResult<Vec<(Listing, Uint128)>> {
+    let mut picked: Vec<(Listing, Uint128)> = vec![];
+    for (collection, id) in ids {
+        let key = listing_key(deps, &collection, id)?;
+        if picked
+            .iter()
+            .any(|(listing, _)| listing.collection == key.0 && listing.nft_id == key.1)
+        {
+            continue;
+        }
+        let Some(listing) = listings().may_load(deps.storage, key)? else {
+            continue;
+        };
+        match buyable_price(&listing, env, denom) {
+            Some(price) if price <= budget => {
+                budget -= price;
+                picked.push((listing, price));
+            }
+            _ => {}
+        }
+    }
+    Ok(picked)
+}
 
-    let msg: ReceiveNftMsg = from_json(&receive_msg.msg)?;
-    match msg {
-        ReceiveNftMsg::NewListing { price } => {
-            receive_new_listing(deps, sender, receive_msg.token_id, price)
+/// Up to `count` of the cheapest listings of `collection` that `budget`
+/// buys at no more than `max_price_each`.
+fn pick_floor(
+    deps: Deps,
+    env: &Env,
+    collection: String,
+    count: u32,
+    max_price_each: Uint128,
+    denom: &Denom,
+    mut budget: Uint128,
+) -> StdResult<Vec<(Listing, Uint128)>> {
+    let collection = deps.api.addr_validate(&collection)?;
+    let mut picked: Vec<(Listing, Uint128)> = vec![];
+    for item in listings()
+        .idx
+        .price
+        .sub_prefix((collection, denom_key(denom)))
+        .range(deps.storage, None, None, Order::Ascending)
+    {
+        if picked.len() >= count as usize {
+            break;
+        }
+        let (_, listing) = item?;
+        let Some(price) = buyable_price(&listing, env, denom) else {
+            continue;
+        };
+        // prices only go up from here
+        if price > max_price_each || price > budget {
+            break;
         }
+        budget -= price;
+        picked.push((listing, price));
     }
+    Ok(picked)
 }
 
-pub fn receive_new_listing(
-    deps: DepsMut,
-    sender: String,
-    id: String,
-    price: Uint256,
+/// Buys every picked listing for `buyer` out of `amount` of `denom`, which
+/// the marketplace already holds, and refunds what is left.
+pub fn receive_buy_many(
+    mut deps: DepsMut,
+    env: Env,
+    buyer: Addr,
+    picked: Vec<(Listing, Uint128)>,
+    amount: Uint128,
+    denom: Denom,
 ) -> Result<Response, ContractError> {
-    let owner = deps.api.addr_validate(&sender)?;
-
-    let new_listing = Listing {
-        nft_id: id.clone(),
-        price,
-        owner,
-    };
-
-    LISTINGS.save(deps.storage, id.clone(), &new_listing)?;
-    let _ = LISTING_COUNTER.update(deps.storage, |counter: u128| -> StdResult<u128> {
-        Ok(counter.checked_add(1u128).unwrap())
-    });
+    if picked.is_empty() {
+        return Err(ContractError::NothingBought {});
+    }
 
-    let res = Response::new()
-        .add_attribute("action", "new listing")
-        .add_attribute("NFT", id)
-        .add_attribute("owner", sender);
+    let count = picked.len();
+    let mut spent = Uint128::zero();
+    let mut res = Response::new()
+        .add_attribute("action", "buy_many")
+        .add_attribute("buyer", buyer.clone());
+    for (listing, price) in picked {
+        spent = spent.checked_add(price)?;
+        res = res.add_attribute("NFT", listing.nft_id.clone());
+        res = settle_listing(deps.branch(), &env, res, listing, buyer.clone(), price)?;
+    }
 
-    Ok(res)
+    let refund = amount.checked_sub(spent)?;
+    if !refund.is_zero() {
+        res = res.add_message(payment_msg(&denom, &buyer, refund)?);
+    }
+    Ok(res
+        .add_attribute("count", count.to_string())
+        .add_attribute("spent", spent)
+        .add_attribute("refund", refund))
 }
 
-#[cfg_attr(not(feature = "library"), entry_point)]
-pub fn migrate(_deps: DepsMut, _env: Env, _msg: Empty) -> Result<Response, ContractError> {
-    Ok(Response::default())
+/// A token changing hands for `price` of `denom`
+pub struct Trade {
+    pub collection: Addr,
+    pub token_id: String,
+    pub seller: Addr,
+    pub buyer: Addr,
+    pub denom: Denom,
+    pub price: Uint128,
 }
 
-#[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
-    match msg {
-        QueryMsg::GetListing { id } => to_json_binary(&get_listing(deps, id)?),
-        QueryMsg::GetListingsBySeller {
-            seller,
-            from_index,
-            limit,
-        } => to_json_binary(&get_listings_seller(deps, seller, from_index, limit)?),
-        QueryMsg::GetAllListings { from_index, limit } => {
-            to_json_binary(&get_all_listings(deps, from_index, limit)?)
+/// Splits a sale price between the protocol fee, the collection's royalty
+/// and the seller, adding the payouts to `res`, and records the sale.
+pub fn distribute_sale(
+    deps: DepsMut,
+    env: &Env,
+    mut res: Response,
+    trade: Trade,
+) -> Result<Response, ContractError> {
+    let Trade {
+        collection,
+        token_id,
+        seller,
+        buyer,
+        denom,
+        price,
+    } = trade;
+    let config = CONFIG.load(deps.storage)?;
+    let mut proceeds = price;
+    let mut fee_paid = Uint128::zero();
+    let mut royalty_paid = Uint128::zero();
+
+    let fee = price.multiply_ratio(config.fee_bps, 10_000u128);
+    if let (Some(collector), false) = (&config.fee_collector, fee.is_zero()) {
+        proceeds -= fee;
+        fee_paid = fee;
+        res = res
+            .add_message(payment_msg(&denom, collector, fee)?)
+            .add_attribute("fee_amount", fee);
+    }
+
+    if let Some((recipient, royalty)) = query_royalty(deps.as_ref(), &collection, &token_id, price)?
+    {
+        // the fee comes first, the royalty gets at most what is left
+        let royalty = royalty.min(proceeds);
+        if !royalty.is_zero() {
+            proceeds -= royalty;
+            royalty_paid = royalty;
+            record_royalty(deps.storage, &collection, &denom, royalty)?;
+            res = res
+                .add_message(payment_msg(&denom, &recipient, royalty)?)
+                .add_attribute("royalty_recipient", recipient)
+                .add_attribute("royalty_amount", royalty);
         }
-        QueryMsg::GetListingCount {} => to_json_binary(&get_listing_count(deps)?),
     }
-}
 
-pub fn get_listing_count(deps: Deps) -> StdResult<u128> {
-    Ok(LISTING_COUNTER.load(deps.storage)?)
-}
+    if !proceeds.is_zero() {
+        res = res.add_message(payment_msg(&denom, &seller, proceeds)?);
+    }
 
-pub fn get_listing(deps: Deps, id: String) -> StdResult<Listing> {
-    let listing = LISTINGS.load(deps.storage, id)?;
-    Ok(listing)
+    let sale = Sale {
+        id: SALE_COUNTER.may_load(deps.storage)?.unwrap_or_default() + 1,
+        collection,
+        token_id,
+        seller,
+        buyer,
+        price,
+        denom,
+        royalty: royalty_paid,
+        fee: fee_paid,
+        time: env.block.time,
+    };
+    record_sale(deps.storage, &sale)?;
+
+    Ok(res
+        .add_attribute("seller_amount", proceeds)
+        .add_attribute("sale_id", sale.id.to_string()))
 }
 
-pub fn get_listings_seller(
-    deps: Deps,
-    seller: String,
-    from_index: Option<u64>,
-    limit: Option<u64>,
-) -> StdResult<Vec<Listing>> {
-    let from_index = from_index.unwrap_or(0);
-    let limit = limit.unwrap_or(10);
+/// Appends `sale` to the history and adds it to the collection's totals.
+fn record_sale(storage: &mut dyn Storage, sale: &Sale) -> Result<(), ContractError> {
+    SALE_COUNTER.save(storage, &sale.id)?;
+    sales().save(storage, sale.id, sale)?;
 
-    let listings: StdResult<Vec<Listing>> = LISTINGS
-        .range(deps.storage, None, None, Order::Ascending)
-        .skip(from_index as usize)
-        .take(limit as usize)
-        .filter(|item| item.as_ref().unwrap().1.owner == seller)
-        .map(|item| item.map(|(_, listing)| listing))
-        .collect();
-    listings
+    let mut stats = COLLECTION_STATS
+        .may_load(storage, sale.collection.clone())?
+        .unwrap_or_default();
+    stats.sale_count += 1;
+    stats.last_sale = Some(sale.id);
+    COLLECTION_STATS.save(storage, sale.collection.clone(), &stats)?;
+
+    let key = (sale.collection.clone(), denom_key(&sale.denom));
+    let mut volume = VOLUME.may_load(storage, key.clone())?.unwrap_or(Volume {
+        denom: sale.denom.clone(),
+        amount: Uint128::zero(),
+    });
+    volume.amount = volume.amount.checked_add(sale.price)?;
+    Ok(VOLUME.save(storage, key, &volume)?)
 }
 
-pub fn get_all_listings(
+/// The royalty owed on a sale of `token_id` at `sale_price`, if `collection`
+/// implements cw2981 and asks for one. Collections that do not answer the
+/// queries owe nothing; the royalty never exceeds the sale price.
+pub fn query_royalty(
     deps: Deps,
-    from_index: Option<u64>,
-    limit: Option<u64>,
-) -> StdResult<Vec<Listing>> {
-    let from_index = from_index.unwrap_or(0);
-    let limit = limit.unwrap_or(10);
+    collection: &Addr,
+    token_id: &str,
+    sale_price: Uint128,
+) -> StdResult<Option<(Addr, Uint128)>> {
+    let check: StdResult<CheckRoyaltiesResponse> = deps
+        .querier
+        .query_wasm_smart(collection, &Cw2981QueryMsg::CheckRoyalties {});
+    if !check.is_ok_and(|check| check.royalty_payments) {
+        return Ok(None);
+    }
 
-    let listings: StdResult<Vec<Listing>> = LISTINGS
-        .range(deps.storage, None, None, Order::Ascending)
-        .skip(from_index as usize)
-        .take(limit as usize)
-        .map(|item| item.map(|(_, listing)| listing))
-        .collect();
-    listings
-}
+    let info: RoyaltiesInfoResponse = deps.querier.query_wasm_smart(
+        collection,
+        &Cw2981QueryMsg::RoyaltyInfo {
+            token_id: token_id.to_string(),
+            sale_price,
+        },
+    )?;
+    if info.royalty_amount.is_zero() || info.address.is_empty() {
+        return Ok(None);
+    }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info, MOCK_CONTRACT_ADDR};
-    use cosmwasm_std::{from_json, Addr};
+    Ok(Some((
+        deps.api.addr_validate(&info.address)?,
+        info.royalty_amount.min(sale_price),
+    )))
+}
 
-    #[test]
-    fn instantiate_contract() {
-        let mut deps = mock_dependencies();
+fn record_royalty(
+    storage: &mut dyn Storage,
+    collection: &Addr,
+    denom: &Denom,
+    amount: Uint128,
+) -> StdResult<()> {
+    let key = denom_key(denom);
+    let mut total = ROYALTIES_PAID
+        .may_load(storage, (collection.clone(), key.clone()))?
+        .unwrap_or(RoyaltyTotal {
+            denom: denom.clone(),
+            amount: Uint128::zero(),
+        });
+    total.amount = total.amount.checked_add(amount)?;
+    ROYALTIES_PAID.save(storage, (collection.clone(), key), &total)
+}
+
+/// Pays `amount` of `denom` out of the marketplace to `recipient`.
+pub fn payment_msg(denom: &Denom, recipient: &Addr, amount: Uint128) -> StdResult<CosmosMsg> {
+    match denom {
+        Denom::Native(denom) => Ok(BankMsg::Send {
+            to_address: recipient.to_string(),
+            amount: coins(amount.u128(), denom),
+        }
+        .into()),
+        Denom::Cw20(address) => Cw20Contract(address.clone()).call(Cw20ExecuteMsg::Transfer {
+            recipient: recipient.to_string(),
+            amount,
+        }),
+    }
+}
+
+pub fn execute_receive_nft(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    receive_msg: Cw721ReceiveMsg,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    // info.sender is the NFT contract Address
+    let collection = info.sender;
+    let sender = receive_msg.sender.clone();
+
+    let msg: ReceiveNftMsg = from_json(&receive_msg.msg)?;
+    match msg {
+        ReceiveNftMsg::NewListing {
+            price,
+            denom,
+            expires_at,
+        } => {
+            assert_collection(deps.as_ref(), &collection)?;
+            let denom = resolve_denom(deps.as_ref(), &config, denom)?;
+            if expires_at.is_some_and(|expires_at| expires_at <= env.block.time) {
+                return Err(ContractError::InvalidExpiration {});
+            }
+            receive_new_listing(
+                deps,
+                collection,
+                sender,
+                receive_msg.token_id,
+                price,
+                denom,
+                expires_at,
+            )
+        }
+        // the offer pins the collection, it need not still be allowlisted
+        ReceiveNftMsg::AcceptOffer { id } => {
+            receive_accept_offer(deps, env, collection, sender, receive_msg.token_id, id)
+        }
+        ReceiveNftMsg::NewAuction {
+            kind,
+            end_time,
+            denom,
+        } => {
+            assert_collection(deps.as_ref(), &collection)?;
+            let denom = resolve_denom(deps.as_ref(), &config, denom)?;
+            receive_new_auction(
+                deps,
+                env,
+                collection,
+                sender,
+                receive_msg.token_id,
+                kind,
+                end_time,
+                denom,
+            )
+        }
+    }
+}
+
+/// Whether tokens of `collection` can be listed, auctioned or bid on.
+fn assert_collection(deps: Deps, collection: &Addr) -> Result<(), ContractError> {
+    if CONFIG.load(deps.storage)?.open || COLLECTIONS.has(deps.storage, collection.clone()) {
+        return Ok(());
+    }
+    Err(ContractError::CollectionNotAccepted {
+        address: collection.to_string(),
+    })
+}
+
+/// The denom a new listing or auction is priced in: an accepted native
+/// denom or cw20, or the configured cw20 by default.
+fn resolve_denom(
+    deps: Deps,
+    config: &Config,
+    denom: Option<Denom>,
+) -> Result<Denom, ContractError> {
+    match denom {
+        Some(Denom::Native(denom)) => {
+            assert_native(deps, &denom)?;
+            Ok(Denom::Native(denom))
+        }
+        Some(Denom::Cw20(address)) => {
+            let address = deps.api.addr_validate(address.as_str())?;
+            if !ACCEPTED_CW20S.has(deps.storage, address.clone()) {
+                return Err(ContractError::Cw20NotAccepted {
+                    address: address.into_string(),
+                });
+            }
+            Ok(Denom::Cw20(address))
+        }
+        None => Ok(Denom::Cw20(config.cw20_address.clone())),
+    }
+}
+
+fn assert_native(deps: Deps, denom: &str) -> Result<(), ContractError> {
+    if !ACCEPTED_NATIVES.has(deps.storage, denom.to_string()) {
+        return Err(ContractError::NativeNotAccepted {
+            denom: denom.to_string(),
+        });
+    }
+    Ok(())
+}
+
+fn transfer_nft_msg(collection: &Addr, recipient: &Addr, token_id: &str) -> StdResult<CosmosMsg> {
+    Ok(WasmMsg::Execute {
+        contract_addr: collection.to_string(),
+        msg: to_json_binary(&Cw721ExecuteMsg::TransferNft {
+            recipient: recipient.to_string(),
+            token_id: token_id.to_string(),
+        })?,
+        funds: vec![],
+    }
+    .into())
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn receive_new_auction(
+    deps: DepsMut,
+    env: Env,
+    collection: Addr,
+    sender: String,
+    nft_id: String,
+    kind: AuctionKind,
+    end_time: Timestamp,
+    denom: Denom,
+) -> Result<Response, ContractError> {
+    let seller = deps.api.addr_validate(&sender)?;
+    if end_time <= env.block.time {
+        return Err(ContractError::InvalidAuction {});
+    }
+    let valid = match &kind {
+        AuctionKind::English {
+            reserve_price,
+            min_increment,
+            extension,
+            max_extension,
+        } => !reserve_price.is_zero() && !min_increment.is_zero() && extension <= max_extension,
+        AuctionKind::Dutch {
+            start_price,
+            end_price,
+        } => !end_price.is_zero() && start_price > end_price,
+    };
+    if !valid {
+        return Err(ContractError::InvalidAuction {});
+    }
+
+    let max_end_time = match &kind {
+        AuctionKind::English { max_extension, .. } => end_time.plus_seconds(*max_extension),
+        AuctionKind::Dutch { .. } => end_time,
+    };
+
+    let id = AUCTION_COUNTER.may_load(deps.storage)?.unwrap_or_default() + 1;
+    AUCTION_COUNTER.save(deps.storage, &id)?;
+
+    let auction = Auction {
+        id,
+        collection,
+        nft_id,
+        seller,
+        denom,
+        kind,
+        start_time: env.block.time,
+        end_time,
+        max_end_time,
+        highest_bid: None,
+    };
+    AUCTIONS.save(deps.storage, id, &auction)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "new_auction")
+        .add_attribute("auction_id", id.to_string())
+        .add_attribute("collection", auction.collection)
+        .add_attribute("NFT", auction.nft_id)
+        .add_attribute("seller", auction.seller)
+        .add_attribute("end_time", auction.end_time.to_string()))
+}
+
+/// The least a bid on `auction` must offer at `now`.
+pub fn min_bid(auction: &Auction, now: Timestamp) -> StdResult<Uint128> {
+    match &auction.kind {
+        AuctionKind::English {
+            reserve_price,
+            min_increment,
+            ..
+        } => match &auction.highest_bid {
+            Some(bid) => Ok(bid.amount.checked_add(*min_increment)?),
+            None => Ok(*reserve_price),
+        },
+        AuctionKind::Dutch {
+            start_price,
+            end_price,
+        } => {
+            if now >= auction.end_time {
+                return Ok(*end_price);
+            }
+            let elapsed = now.seconds() - auction.start_time.seconds();
+            let duration = auction.end_time.seconds() - auction.start_time.seconds();
+            Ok(*start_price - (*start_price - *end_price).multiply_ratio(elapsed, duration))
+        }
+    }
+}
+
+/// Places a bid of `amount` that the marketplace already holds. An English
+/// bid is escrowed and refunds the one it beats; a Dutch bid buys the token
+/// at the current price and gets the excess back.
+pub fn receive_bid(
+    mut deps: DepsMut,
+    env: Env,
+    id: u64,
+    bidder: Addr,
+    amount: Uint128,
+    denom: Denom,
+) -> Result<Response, ContractError> {
+    let mut auction = AUCTIONS.load(deps.storage, id)?;
+    if denom != auction.denom {
+        return Err(ContractError::IncorrectDenom {});
+    }
+    let now = env.block.time;
+    if now >= auction.end_time {
+        return Err(ContractError::AuctionEnded { id });
+    }
+    let min = min_bid(&auction, now)?;
+    if amount < min {
+        return Err(ContractError::BidTooLow { min });
+    }
+
+    let mut res = Response::new()
+        .add_attribute("action", "place_bid")
+        .add_attribute("auction_id", id.to_string())
+        .add_attribute("bidder", bidder.clone());
+
+    match auction.kind {
+        AuctionKind::English { extension, .. } => {
+            if let Some(outbid) = auction.highest_bid.take() {
+                res = res.add_message(payment_msg(&denom, &outbid.bidder, outbid.amount)?);
+            }
+            auction.highest_bid = Some(Bid { bidder, amount });
+            // keep bidding open for a while after a late bid, up to the cap
+            let extended = now.plus_seconds(extension).min(auction.max_end_time);
+            if extended > auction.end_time {
+                auction.end_time = extended;
+            }
+            AUCTIONS.save(deps.storage, id, &auction)?;
+
+            Ok(res
+                .add_attribute("amount", amount)
+                .add_attribute("end_time", auction.end_time.to_string()))
+        }
+        AuctionKind::Dutch { .. } => {
+            AUCTIONS.remove(deps.storage, id);
+            res = res
+                .add_attribute("amount", min)
+                .add_message(transfer_nft_msg(
+                    &auction.collection,
+                    &bidder,
+                    &auction.nft_id,
+                )?);
+            if amount > min {
+                res = res.add_message(payment_msg(&denom, &bidder, amount - min)?);
+            }
+            res = distribute_sale(
+                deps.branch(),
+                &env,
+                res,
+                Trade {
+                    collection: auction.collection,
+                    token_id: auction.nft_id,
+                    seller: auction.seller,
+                    buyer: bidder,
+                    denom,
+                    price: min,
+                },
+            )?;
+            Ok(res)
+        }
+    }
+}
+
+pub fn execute_settle_auction(
+    mut deps: DepsMut,
+    env: Env,
+    id: u64,
+) -> Result<Response, ContractError> {
+    let auction = AUCTIONS.load(deps.storage, id)?;
+    if env.block.time < auction.end_time {
+        return Err(ContractError::AuctionNotEnded { id });
+    }
+    AUCTIONS.remove(deps.storage, id);
+
+    let res = Response::new()
+        .add_attribute("action", "settle_auction")
+        .add_attribute("auction_id", id.to_string());
+
+    match auction.highest_bid {
+        Some(bid) => {
+            let res = res
+                .add_attribute("buyer", bid.bidder.clone())
+                .add_attribute("amount", bid.amount)
+                .add_message(transfer_nft_msg(
+                    &auction.collection,
+                    &bid.bidder,
+                    &auction.nft_id,
+                )?);
+            distribute_sale(
+                deps.branch(),
+                &env,
+                res,
+                Trade {
+                    collection: auction.collection,
+                    token_id: auction.nft_id,
+                    seller: auction.seller,
+                    buyer: bid.bidder,
+                    denom: auction.denom,
+                    price: bid.amount,
+                },
+            )
+        }
+        None => Ok(res.add_message(transfer_nft_msg(
+            &auction.collection,
+            &auction.seller,
+            &auction.nft_id,
+        )?)),
+    }
+}
+
+pub fn execute_cancel_auction(
+    deps: DepsMut,
+    info: MessageInfo,
+    id: u64,
+) -> Result<Response, ContractError> {
+    let auction = AUCTIONS.load(deps.storage, id)?;
+    if info.sender != auction.seller {
+        return Err(ContractError::Unauthorized {});
+    }
+    if auction.highest_bid.is_some() {
+        return Err(ContractError::AuctionHasBids { id });
+    }
+    AUCTIONS.remove(deps.storage, id);
+
+    Ok(Response::new()
+        .add_attribute("action", "cancel_auction")
+        .add_attribute("auction_id", id.to_string())
+        .add_message(transfer_nft_msg(
+            &auction.collection,
+            &auction.seller,
+            &auction.nft_id,
+        )?))
+}
+
+pub fn receive_new_listing(
+    deps: DepsMut,
+    collection: Addr,
+    sender: String,
+    id: String,
+    price: Uint256,
+    denom: Denom,
+    expires_at: Option<Timestamp>,
+) -> Result<Response, ContractError> {
+    let owner = deps.api.addr_validate(&sender)?;
+
+    let new_listing = Listing {
+        collection: collection.clone(),
+        nft_id: id.clone(),
+        price,
+        owner,
+        denom,
+        expires_at,
+    };
+
+    listings().save(deps.storage, (collection.clone(), id.clone()), &new_listing)?;
+    let _ = LISTING_COUNTER.update(deps.storage, |counter: u128| -> StdResult<u128> {
+        Ok(counter.checked_add(1u128).unwrap())
+    });
+
+    let res = Response::new()
+        .add_attribute("action", "new listing")
+        .add_attribute("collection", collection)
+        .add_attribute("NFT", id)
+        .add_attribute("owner", sender);
+
+    Ok(res)
+}
+
+/// A listing as stored before listings were keyed by collection
+#[cosmwasm_schema::cw_serde]
+struct LegacyListing {
+    nft_id: String,
+    price: Uint256,
+    owner: Addr,
+    #[serde(default)]
+    denom: Option<Denom>,
+    #[serde(default)]
+    expires_at: Option<Timestamp>,
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(deps: DepsMut, _env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
+    let stored = get_contract_version(deps.storage)?;
+    if stored.contract != CONTRACT_NAME
+        || stored.version.parse::<Version>()? > CONTRACT_VERSION.parse::<Version>()?
+    {
+        return Err(ContractError::CannotMigrate {
+            contract: stored.contract,
+            version: stored.version,
+        });
+    }
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    if cw_ownable::get_ownership(deps.storage)?.owner.is_none() {
+        if let Some(owner) = msg.owner {
+            cw_ownable::initialize_owner(deps.storage, deps.api, Some(&owner))?;
+        }
+    }
+
+    let config = CONFIG.load(deps.storage)?;
+    if ACCEPTED_CW20S.is_empty(deps.storage) {
+        ACCEPTED_CW20S.save(deps.storage, config.cw20_address.clone(), &Empty {})?;
+    }
+    if COLLECTIONS.is_empty(deps.storage) {
+        COLLECTIONS.save(deps.storage, config.cw404_address.clone(), &Empty {})?;
+    }
+    for denom in msg.accepted_natives.unwrap_or_default() {
+        ACCEPTED_NATIVES.save(deps.storage, denom, &Empty {})?;
+    }
+
+    // legacy listings all belong to the original collection, and those
+    // created before per-listing denoms are priced in the configured cw20
+    let legacy: Map<String, LegacyListing> = Map::new("listings");
+    let legacy_listings = legacy
+        .range(deps.storage, None, None, Order::Ascending)
+        .take(msg.limit.unwrap_or(MIGRATE_LIMIT) as usize)
+        .collect::<StdResult<Vec<_>>>()?;
+    let migrated = legacy_listings.len();
+    for (id, listing) in legacy_listings {
+        legacy.remove(deps.storage, id.clone());
+        listings().save(
+            deps.storage,
+            (config.cw404_address.clone(), id),
+            &Listing {
+                collection: config.cw404_address.clone(),
+                nft_id: listing.nft_id,
+                price: listing.price,
+                owner: listing.owner,
+                denom: listing
+                    .denom
+                    .unwrap_or_else(|| Denom::Cw20(config.cw20_address.clone())),
+                expires_at: listing.expires_at,
+            },
+        )?;
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", "migrate")
+        .add_attribute("from_version", stored.version)
+        .add_attribute("to_version", CONTRACT_VERSION)
+        .add_attribute("migrated", migrated.to_string())
+        .add_attribute("complete", legacy.is_empty(deps.storage).to_string()))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::Config {} => to_json_binary(&get_config(deps)?),
+        QueryMsg::Ownership {} => to_json_binary(&cw_ownable::get_ownership(deps.storage)?),
+        QueryMsg::GetListing { collection, id } => {
+            to_json_binary(&get_listing(deps, collection, id)?)
+        }
+        QueryMsg::GetCollectionListings {
+            collection,
+            start_after,
+            limit,
+        } => to_json_binary(&get_collection_listings(
+            deps,
+            collection,
+            start_after,
+            limit,
+        )?),
+        QueryMsg::GetListingsBySeller {
+            seller,
+            start_after,
+            limit,
+        } => to_json_binary(&get_listings_seller(deps, seller, start_after, limit)?),
+        QueryMsg::GetAllListings { start_after, limit } => {
+            to_json_binary(&get_all_listings(deps, start_after, limit)?)
+        }
+        QueryMsg::ListingsByPrice {
+            collection,
+            denom,
+            min,
+            max,
+            start_after,
+            limit,
+            order,
+        } => to_json_binary(&get_listings_by_price(
+            deps,
+            collection,
+            denom,
+            min,
+            max,
+            start_after,
+            limit,
+            order.unwrap_or(SortOrder::Ascending),
+        )?),
+        QueryMsg::FloorPrice { collection, denom } => {
+            to_json_binary(&get_floor_price(deps, env, collection, denom)?)
+        }
+        QueryMsg::GetListingCount {} => to_json_binary(&get_listing_count(deps)?),
+        QueryMsg::RoyaltiesPaid { collection } => {
+            to_json_binary(&get_royalties_paid(deps, collection)?)
+        }
+        QueryMsg::GetOffer { id } => to_json_binary(&offers().load(deps.storage, id)?),
+        QueryMsg::GetOffersByNft {
+            collection,
+            nft_id,
+            start_after,
+            limit,
+        } => to_json_binary(&get_offers_nft(
+            deps,
+            collection,
+            nft_id,
+            start_after,
+            limit,
+        )?),
+        QueryMsg::GetCollectionOffers {
+            collection,
+            start_after,
+            limit,
+        } => to_json_binary(&get_offers_nft(
+            deps,
+            collection,
+            String::new(),
+            start_after,
+            limit,
+        )?),
+        QueryMsg::SalesHistory {
+            collection,
+            token_id,
+            start_after,
+            limit,
+        } => to_json_binary(&get_sales_history(
+            deps,
+            collection,
+            token_id,
+            start_after,
+            limit,
+        )?),
+        QueryMsg::CollectionStats { collection } => {
+            to_json_binary(&get_collection_stats(deps, env, collection)?)
+        }
+        QueryMsg::Collections { start_after, limit } => {
+            to_json_binary(&get_collections(deps, start_after, limit)?)
+        }
+        QueryMsg::GetOffersByBidder {
+            bidder,
+            start_after,
+            limit,
+        } => to_json_binary(&get_offers_bidder(deps, bidder, start_after, limit)?),
+        QueryMsg::GetAuction { id } => to_json_binary(&get_auction(deps, env, id)?),
+        QueryMsg::GetAllAuctions { start_after, limit } => {
+            to_json_binary(&get_all_auctions(deps, start_after, limit)?)
+        }
+    }
+}
+
+pub fn get_auction(deps: Deps, env: Env, id: u64) -> StdResult<AuctionResponse> {
+    let auction = AUCTIONS.load(deps.storage, id)?;
+    Ok(AuctionResponse {
+        // nothing can outbid a bid at the very top
+        min_bid: min_bid(&auction, env.block.time).unwrap_or(Uint128::MAX),
+        ended: env.block.time >= auction.end_time,
+        auction,
+    })
+}
+
+pub fn get_all_auctions(
+    deps: Deps,
+    start_after: Option<u64>,
+    limit: Option<u64>,
+) -> StdResult<Vec<Auction>> {
+    let limit = limit.unwrap_or(10);
+
+    AUCTIONS
+        .range(
+            deps.storage,
+            start_after.map(Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .take(limit as usize)
+        .map(|item| item.map(|(_, auction)| auction))
+        .collect()
+}
+
+pub fn get_config(deps: Deps) -> StdResult<ConfigResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    let accepted_cw20s = ACCEPTED_CW20S
+        .keys(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<Addr>>>()?;
+    let accepted_natives = ACCEPTED_NATIVES
+        .keys(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<String>>>()?;
+    Ok(ConfigResponse {
+        cw404_address: config.cw404_address,
+        cw20_address: config.cw20_address,
+        fee_bps: config.fee_bps,
+        fee_collector: config.fee_collector,
+        accepted_cw20s,
+        accepted_natives,
+        open: config.open,
+    })
+}
+
+pub fn get_sales_history(
+    deps: Deps,
+    collection: String,
+    token_id: Option<String>,
+    start_after: Option<u64>,
+    limit: Option<u64>,
+) -> StdResult<Vec<Sale>> {
+    let collection = deps.api.addr_validate(&collection)?;
+    let end = start_after.map(Bound::exclusive);
+    let limit = limit.unwrap_or(10) as usize;
+
+    let history = match token_id {
+        Some(token_id) => sales().idx.token.prefix((collection, token_id)).range(
+            deps.storage,
+            None,
+            end,
+            Order::Descending,
+        ),
+        None => sales().idx.collection.prefix(collection).range(
+            deps.storage,
+            None,
+            end,
+            Order::Descending,
+        ),
+    };
+    history
+        .take(limit)
+        .map(|item| item.map(|(_, sale)| sale))
+        .collect()
+}
+
+pub fn get_collection_stats(
+    deps: Deps,
+    env: Env,
+    collection: String,
+) -> StdResult<CollectionStatsResponse> {
+    let collection = deps.api.addr_validate(&collection)?;
+    let stats = COLLECTION_STATS
+        .may_load(deps.storage, collection.clone())?
+        .unwrap_or_default();
+    let volume = VOLUME
+        .prefix(collection.clone())
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(_, volume)| volume))
+        .collect::<StdResult<Vec<_>>>()?;
+    let last_sale = stats
+        .last_sale
+        .map(|id| sales().load(deps.storage, id))
+        .transpose()?;
+
+    // walk back through the history until a sale is older than a day
+    let since = env.block.time.minus_seconds(24 * 60 * 60);
+    let mut sale_count_24h = 0;
+    let mut volume_24h: Vec<Volume> = vec![];
+    for item in
+        sales()
+            .idx
+            .collection
+            .prefix(collection)
+            .range(deps.storage, None, None, Order::Descending)
+    {
+        let (_, sale) = item?;
+        if sale.time <= since {
+            break;
+        }
+        sale_count_24h += 1;
+        match volume_24h.iter_mut().find(|v| v.denom == sale.denom) {
+            Some(volume) => volume.amount = volume.amount.checked_add(sale.price)?,
+            None => volume_24h.push(Volume {
+                denom: sale.denom,
+                amount: sale.price,
+            }),
+        }
+    }
+
+    Ok(CollectionStatsResponse {
+        sale_count: stats.sale_count,
+        volume,
+        last_sale,
+        sale_count_24h,
+        volume_24h,
+    })
+}
+
+pub fn get_collections(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u64>,
+) -> StdResult<CollectionsResponse> {
+    let start_after = start_after
+        .map(|addr| deps.api.addr_validate(&addr))
+        .transpose()?;
+    let limit = limit.unwrap_or(10);
+
+    let collections = COLLECTIONS
+        .keys(
+            deps.storage,
+            start_after.map(Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .take(limit as usize)
+        .collect::<StdResult<Vec<Addr>>>()?;
+    Ok(CollectionsResponse {
+        open: CONFIG.load(deps.storage)?.open,
+        collections,
+    })
+}
+
+pub fn get_listing_count(deps: Deps) -> StdResult<u128> {
+    LISTING_COUNTER.load(deps.storage)
+}
+
+pub fn get_royalties_paid(deps: Deps, collection: String) -> StdResult<Vec<RoyaltyTotal>> {
+    let collection = deps.api.addr_validate(&collection)?;
+    ROYALTIES_PAID
+        .prefix(collection)
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(_, total)| total))
+        .collect()
+}
+
+pub fn get_listing(deps: Deps, collection: String, id: String) -> StdResult<Listing> {
+    let listing = listings().load(deps.storage, listing_key(deps, &collection, id)?)?;
+    Ok(listing)
+}
+
+pub fn get_collection_listings(
+    deps: Deps,
+    collection: String,
+    start_after: Option<String>,
+    limit: Option<u64>,
+) -> StdResult<Vec<Listing>> {
+    let collection = deps.api.addr_validate(&collection)?;
+    let limit = limit.unwrap_or(10);
+
+    listings()
+        .prefix(collection)
+        .range(
+            deps.storage,
+            start_after.map(Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .take(limit as usize)
+        .map(|item| item.map(|(_, listing)| listing))
+        .collect()
+}
+
+pub fn get_listings_seller(
+    deps: Deps,
+    seller: String,
+    start_after: Option<(String, String)>,
+    limit: Option<u64>,
+) -> StdResult<Vec<Listing>> {
+    let seller = deps.api.addr_validate(&seller)?;
+    let start_after = start_after
+        .map(|(collection, id)| listing_key(deps, &collection, id))
+        .transpose()?;
+    let limit = limit.unwrap_or(10);
+
+    listings()
+        .idx
+        .seller
+        .prefix(seller)
+        .range(
+            deps.storage,
+            start_after.map(Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .take(limit as usize)
+        .map(|item| item.map(|(_, listing)| listing))
+        .collect()
+}
+
+pub fn get_all_listings(
+    deps: Deps,
+    start_after: Option<(String, String)>,
+    limit: Option<u64>,
+) -> StdResult<Vec<Listing>> {
+    let start_after = start_after
+        .map(|(collection, id)| listing_key(deps, &collection, id))
+        .transpose()?;
+    let limit = limit.unwrap_or(10);
+
+    listings()
+        .range(
+            deps.storage,
+            start_after.map(Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .take(limit as usize)
+        .map(|item| item.map(|(_, listing)| listing))
+        .collect()
+}
+
+/// `price` as stored in the price index after the collection and denom:
+/// length prefixed big-endian bytes.
+fn price_index_key(price: Uint256) -> Vec<u8> {
+    let bytes = price.to_be_bytes();
+    let mut key = (bytes.len() as u16).to_be_bytes().to_vec();
+    key.extend_from_slice(&bytes);
+    key
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn get_listings_by_price(
+    deps: Deps,
+    collection: String,
+    denom: Denom,
+    min: Option<Uint256>,
+    max: Option<Uint256>,
+    start_after: Option<String>,
+    limit: Option<u64>,
+    order: SortOrder,
+) -> StdResult<Vec<Listing>> {
+    let collection = deps.api.addr_validate(&collection)?;
+    let limit = limit.unwrap_or(10);
+
+    // every index entry continues past the price with the listing key,
+    // whose first byte is below 0xff
+    let mut lower = min.map(|min| Bound::InclusiveRaw(price_index_key(min)));
+    let mut upper = max.map(|max| {
+        let mut key = price_index_key(max);
+        key.push(0xff);
+        Bound::ExclusiveRaw(key)
+    });
+    if let Some(id) = start_after {
+        let listing = listings().load(deps.storage, (collection.clone(), id.clone()))?;
+        let cursor = Bound::exclusive((
+            listing.price.to_be_bytes().to_vec(),
+            (collection.clone(), id),
+        ));
+        match order {
+            SortOrder::Ascending => lower = Some(cursor),
+            SortOrder::Descending => upper = Some(cursor),
+        }
+    }
+
+    listings()
+        .idx
+        .price
+        .sub_prefix((collection, denom_key(&denom)))
+        .range(deps.storage, lower, upper, order.into())
+        .take(limit as usize)
+        .map(|item| item.map(|(_, listing)| listing))
+        .collect()
+}
+
+pub fn get_floor_price(
+    deps: Deps,
+    env: Env,
+    collection: String,
+    denom: Denom,
+) -> StdResult<Option<Listing>> {
+    let collection = deps.api.addr_validate(&collection)?;
+
+    for item in listings()
+        .idx
+        .price
+        .sub_prefix((collection, denom_key(&denom)))
+        .range(deps.storage, None, None, Order::Ascending)
+    {
+        let (_, listing) = item?;
+        if !is_expired(&listing, &env) {
+            return Ok(Some(listing));
+        }
+    }
+    Ok(None)
+}
+
+/// Offers on `nft_id` of `collection`; the empty id holds the collection
+/// offers.
+pub fn get_offers_nft(
+    deps: Deps,
+    collection: String,
+    nft_id: String,
+    start_after: Option<u64>,
+    limit: Option<u64>,
+) -> StdResult<Vec<Offer>> {
+    let collection = deps.api.addr_validate(&collection)?;
+    let limit = limit.unwrap_or(10);
+
+    offers()
+        .idx
+        .nft
+        .prefix((collection, nft_id))
+        .range(
+            deps.storage,
+            start_after.map(Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .take(limit as usize)
+        .map(|item| item.map(|(_, offer)| offer))
+        .collect()
+}
+
+pub fn get_offers_bidder(
+    deps: Deps,
+    bidder: String,
+    start_after: Option<u64>,
+    limit: Option<u64>,
+) -> StdResult<Vec<Offer>> {
+    let bidder = deps.api.addr_validate(&bidder)?;
+    let limit = limit.unwrap_or(10);
+
+    offers()
+        .idx
+        .bidder
+        .prefix(bidder)
+        .range(
+            deps.storage,
+            start_after.map(Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .take(limit as usize)
+        .map(|item| item.map(|(_, offer)| offer))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmwasm_std::testing::{
+        mock_dependencies, mock_env, mock_info, MockApi, MockQuerier, MockStorage,
+    };
+    use cosmwasm_std::{
+        from_json, Addr, ContractResult, OwnedDeps, SystemError, SystemResult, WasmQuery,
+    };
 
+    /// A marketplace for the "cw404" collection, priced in "cw20" by default
+    /// or "uxion", and without a protocol fee
+    fn setup() -> OwnedDeps<MockStorage, MockApi, MockQuerier> {
+        let mut deps = mock_dependencies();
         let msg = InstantiateMsg {
-            cw404_address: String::from(MOCK_CONTRACT_ADDR),
-            cw20_address: String::from(MOCK_CONTRACT_ADDR),
+            cw404_address: String::from("cw404"),
+            cw20_address: String::from("cw20"),
+            fee_bps: 0,
+            fee_collector: None,
+            accepted_natives: Some(vec![String::from("uxion")]),
         };
-        let info = mock_info("creator", &[]);
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        deps
+    }
 
-        let _res = instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+    fn set_fee(deps: DepsMut, fee_bps: u16) {
+        let msg = ExecuteMsg::UpdateConfig {
+            fee_bps: Some(fee_bps),
+            fee_collector: Some(String::from("collector")),
+            add_cw20s: None,
+            remove_cw20s: None,
+            add_natives: None,
+            remove_natives: None,
+            open: None,
+            add_collections: None,
+            remove_collections: None,
+        };
+        execute(deps, mock_env(), mock_info("creator", &[]), msg).unwrap();
     }
 
     #[test]
-    fn test_receive_list() {
-        let mut deps = mock_dependencies();
+    fn instantiate_contract() {
+        let deps = setup();
 
-        let msg = InstantiateMsg {
-            cw404_address: String::from("cw404"),
-            cw20_address: String::from("cw20"),
-        };
-        let mut info = mock_info("creator", &[]);
+        let config: ConfigResponse =
+            from_json(query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap()).unwrap();
+        assert_eq!(config.cw404_address, Addr::unchecked("cw404"));
+        assert_eq!(config.fee_bps, 0);
+        assert_eq!(config.accepted_natives, vec!["uxion".to_string()]);
+    }
 
-        let _res = instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+    #[test]
+    fn test_receive_list() {
+        let mut deps = setup();
 
         let msg = ExecuteMsg::ReceiveNft(Cw721ReceiveMsg {
             sender: String::from("cw404"),
             token_id: "1".to_string(),
             msg: to_json_binary(&ReceiveNftMsg::NewListing {
                 price: Uint256::from(5u128),
+                denom: None,
+                expires_at: None,
             })
             .unwrap(),
         });
 
-        info.sender = Addr::unchecked("cw404");
-        let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        execute(deps.as_mut(), mock_env(), mock_info("cw404", &[]), msg).unwrap();
 
         // query listings
         let msg = QueryMsg::GetAllListings {
-            from_index: Some(0),
+            start_after: None,
             limit: Some(10),
         };
         let res = query(deps.as_ref(), mock_env(), msg).unwrap();
@@ -339,40 +1989,36 @@ mod tests {
         assert_eq!(
             listings,
             vec![Listing {
+                collection: Addr::unchecked("cw404"),
                 nft_id: "1".to_string(),
                 price: Uint256::from(5u128),
                 owner: Addr::unchecked("cw404"),
+                denom: Denom::Cw20(Addr::unchecked("cw20")),
+                expires_at: None,
             }]
         );
     }
 
     #[test]
     fn test_receive_buy() {
-        let mut deps = mock_dependencies();
-
-        let msg = InstantiateMsg {
-            cw404_address: String::from("cw404"),
-            cw20_address: String::from("cw20"),
-        };
-        let mut info = mock_info("creator", &[]);
-
-        let _res = instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        let mut deps = setup();
 
         let msg = ExecuteMsg::ReceiveNft(Cw721ReceiveMsg {
             sender: String::from("cw404"),
             token_id: "1".to_string(),
             msg: to_json_binary(&ReceiveNftMsg::NewListing {
                 price: Uint256::from(5u128),
+                denom: None,
+                expires_at: None,
             })
             .unwrap(),
         });
 
-        info.sender = Addr::unchecked("cw404");
-        let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        execute(deps.as_mut(), mock_env(), mock_info("cw404", &[]), msg).unwrap();
 
         // query listings
         let msg = QueryMsg::GetAllListings {
-            from_index: Some(0),
+            start_after: None,
             limit: Some(10),
         };
         let res = query(deps.as_ref(), mock_env(), msg).unwrap();
@@ -381,9 +2027,12 @@ mod tests {
         assert_eq!(
             listings,
             vec![Listing {
+                collection: Addr::unchecked("cw404"),
                 nft_id: "1".to_string(),
                 price: Uint256::from(5u128),
                 owner: Addr::unchecked("cw404"),
+                denom: Denom::Cw20(Addr::unchecked("cw20")),
+                expires_at: None,
             }]
         );
 
@@ -391,12 +2040,1169 @@ mod tests {
             sender: String::from("cw20"),
             amount: Uint128::new(5),
             msg: to_json_binary(&ReceiveMsg::Buy {
+                collection: "cw404".to_string(),
+                id: "1".to_string(),
+            })
+            .unwrap(),
+        });
+
+        execute(deps.as_mut(), mock_env(), mock_info("cw20", &[]), msg).unwrap();
+    }
+
+    #[test]
+    fn test_buy_native() {
+        let mut deps = setup();
+
+        // only allowlisted native denoms can price a listing
+        let msg = ExecuteMsg::ReceiveNft(Cw721ReceiveMsg {
+            sender: String::from("seller"),
+            token_id: "1".to_string(),
+            msg: to_json_binary(&ReceiveNftMsg::NewListing {
+                price: Uint256::from(5u128),
+                denom: Some(Denom::Native("ufake".to_string())),
+                expires_at: None,
+            })
+            .unwrap(),
+        });
+        let err = execute(deps.as_mut(), mock_env(), mock_info("cw404", &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::NativeNotAccepted { .. }));
+
+        let msg = ExecuteMsg::ReceiveNft(Cw721ReceiveMsg {
+            sender: String::from("seller"),
+            token_id: "1".to_string(),
+            msg: to_json_binary(&ReceiveNftMsg::NewListing {
+                price: Uint256::from(5u128),
+                denom: Some(Denom::Native("uxion".to_string())),
+                expires_at: None,
+            })
+            .unwrap(),
+        });
+        execute(deps.as_mut(), mock_env(), mock_info("cw404", &[]), msg).unwrap();
+
+        // the cw20 path cannot settle a natively priced listing
+        let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: String::from("buyer"),
+            amount: Uint128::new(5),
+            msg: to_json_binary(&ReceiveMsg::Buy {
+                collection: "cw404".to_string(),
+                id: "1".to_string(),
+            })
+            .unwrap(),
+        });
+        execute(deps.as_mut(), mock_env(), mock_info("cw20", &[]), msg).unwrap_err();
+
+        let buy = ExecuteMsg::Buy {
+            collection: "cw404".to_string(),
+            id: "1".to_string(),
+        };
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("buyer", &coins(4, "uxion")),
+            buy.clone(),
+        )
+        .unwrap_err();
+
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("buyer", &coins(5, "uxion")),
+            buy,
+        )
+        .unwrap();
+        assert_eq!(
+            res.messages[1].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "seller".to_string(),
+                amount: coins(5, "uxion"),
+            })
+        );
+        assert!(listings()
+            .may_load(
+                deps.as_ref().storage,
+                (Addr::unchecked("cw404"), "1".to_string()),
+            )
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_buy_pays_royalty() {
+        let mut deps = setup();
+        deps.querier.update_wasm(|query| match query {
+            WasmQuery::Smart { msg, .. } => {
+                let res = match from_json(msg).unwrap() {
+                    Cw2981QueryMsg::CheckRoyalties {} => to_json_binary(&CheckRoyaltiesResponse {
+                        royalty_payments: true,
+                    }),
+                    Cw2981QueryMsg::RoyaltyInfo { sale_price, .. } => {
+                        to_json_binary(&RoyaltiesInfoResponse {
+                            address: "creator".to_string(),
+                            royalty_amount: sale_price.multiply_ratio(1u128, 10u128),
+                        })
+                    }
+                };
+                SystemResult::Ok(ContractResult::Ok(res.unwrap()))
+            }
+            _ => SystemResult::Err(SystemError::Unknown {}),
+        });
+
+        let msg = ExecuteMsg::ReceiveNft(Cw721ReceiveMsg {
+            sender: String::from("seller"),
+            token_id: "1".to_string(),
+            msg: to_json_binary(&ReceiveNftMsg::NewListing {
+                price: Uint256::from(100u128),
+                denom: None,
+                expires_at: None,
+            })
+            .unwrap(),
+        });
+        execute(deps.as_mut(), mock_env(), mock_info("cw404", &[]), msg).unwrap();
+
+        let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: String::from("buyer"),
+            amount: Uint128::new(100),
+            msg: to_json_binary(&ReceiveMsg::Buy {
+                collection: "cw404".to_string(),
+                id: "1".to_string(),
+            })
+            .unwrap(),
+        });
+        let res = execute(deps.as_mut(), mock_env(), mock_info("cw20", &[]), msg).unwrap();
+
+        let cw20 = Cw20Contract(Addr::unchecked("cw20"));
+        let transfer = |recipient: &str, amount: u128| {
+            cw20.call(Cw20ExecuteMsg::Transfer {
+                recipient: recipient.to_string(),
+                amount: Uint128::new(amount),
+            })
+            .unwrap()
+        };
+        assert_eq!(res.messages[1].msg, transfer("creator", 10));
+        assert_eq!(res.messages[2].msg, transfer("seller", 90));
+        assert!(res
+            .attributes
+            .iter()
+            .any(|a| a.key == "royalty_amount" && a.value == "10"));
+
+        let paid: Vec<RoyaltyTotal> = from_json(
+            query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::RoyaltiesPaid {
+                    collection: "cw404".to_string(),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            paid,
+            vec![RoyaltyTotal {
+                denom: Denom::Cw20(Addr::unchecked("cw20")),
+                amount: Uint128::new(10),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_protocol_fee() {
+        let mut deps = setup();
+
+        set_fee(deps.as_mut(), 250);
+
+        let update = |fee_bps| ExecuteMsg::UpdateConfig {
+            fee_bps: Some(fee_bps),
+            fee_collector: None,
+            add_cw20s: None,
+            remove_cw20s: None,
+            add_natives: None,
+            remove_natives: None,
+            open: None,
+            add_collections: None,
+            remove_collections: None,
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("seller", &[]),
+            update(0),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::Ownership(_)));
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            update(2_000),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::FeeTooHigh { .. }));
+
+        let msg = ExecuteMsg::ReceiveNft(Cw721ReceiveMsg {
+            sender: String::from("seller"),
+            token_id: "1".to_string(),
+            msg: to_json_binary(&ReceiveNftMsg::NewListing {
+                price: Uint256::from(1_000u128),
+                denom: Some(Denom::Native("uxion".to_string())),
+                expires_at: None,
+            })
+            .unwrap(),
+        });
+        execute(deps.as_mut(), mock_env(), mock_info("cw404", &[]), msg).unwrap();
+
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("buyer", &coins(1_000, "uxion")),
+            ExecuteMsg::Buy {
+                collection: "cw404".to_string(),
+                id: "1".to_string(),
+            },
+        )
+        .unwrap();
+        let payouts: Vec<CosmosMsg> = res.messages[1..].iter().map(|m| m.msg.clone()).collect();
+        assert_eq!(
+            payouts,
+            vec![
+                CosmosMsg::Bank(BankMsg::Send {
+                    to_address: "collector".to_string(),
+                    amount: coins(25, "uxion"),
+                }),
+                CosmosMsg::Bank(BankMsg::Send {
+                    to_address: "seller".to_string(),
+                    amount: coins(975, "uxion"),
+                }),
+            ]
+        );
+
+        let config: ConfigResponse =
+            from_json(query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap()).unwrap();
+        assert_eq!(config.fee_bps, 250);
+        assert_eq!(config.accepted_cw20s, vec![Addr::unchecked("cw20")]);
+    }
+
+    #[test]
+    fn test_offers() {
+        let mut deps = setup();
+
+        // a native offer on token 1 and a cw20 offer on the whole collection
+        let msg = ExecuteMsg::MakeOffer {
+            collection: "cw404".to_string(),
+            nft_id: Some("1".to_string()),
+            price: Uint128::new(50),
+            expires: None,
+        };
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("bidder", &coins(40, "uxion")),
+            msg.clone(),
+        )
+        .unwrap_err();
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("bidder", &coins(50, "ufake")),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::NativeNotAccepted { .. }));
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("bidder", &coins(50, "uxion")),
+            msg,
+        )
+        .unwrap();
+
+        let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: String::from("bidder"),
+            amount: Uint128::new(30),
+            msg: to_json_binary(&ReceiveMsg::MakeOffer {
+                collection: "cw404".to_string(),
+                nft_id: None,
+                price: Uint128::new(30),
+                expires: Some(Expiration::AtHeight(mock_env().block.height + 10)),
+            })
+            .unwrap(),
+        });
+        execute(deps.as_mut(), mock_env(), mock_info("cw20", &[]), msg).unwrap();
+
+        let by_bidder: Vec<Offer> = from_json(
+            query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::GetOffersByBidder {
+                    bidder: "bidder".to_string(),
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(by_bidder.len(), 2);
+        let by_nft: Vec<Offer> = from_json(
+            query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::GetOffersByNft {
+                    collection: "cw404".to_string(),
+                    nft_id: "1".to_string(),
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(by_nft, vec![by_bidder[0].clone()]);
+
+        let accept = |token_id: &str, id: u64| {
+            ExecuteMsg::ReceiveNft(Cw721ReceiveMsg {
+                sender: String::from("seller"),
+                token_id: token_id.to_string(),
+                msg: to_json_binary(&ReceiveNftMsg::AcceptOffer { id }).unwrap(),
+            })
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("cw404", &[]),
+            accept("2", 1),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::OfferTokenMismatch { id: 1 }));
+
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("cw404", &[]),
+            accept("1", 1),
+        )
+        .unwrap();
+        assert_eq!(
+            res.messages[1].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "seller".to_string(),
+                amount: coins(50, "uxion"),
+            })
+        );
+
+        // once expired the collection offer cannot be filled, only refunded
+        let mut env = mock_env();
+        env.block.height += 10;
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("cw404", &[]),
+            accept("7", 2),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::OfferExpired { id: 2 }));
+        let res = execute(
+            deps.as_mut(),
+            env,
+            mock_info("anyone", &[]),
+            ExecuteMsg::CancelOffer { id: 2 },
+        )
+        .unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            Cw20Contract(Addr::unchecked("cw20"))
+                .call(Cw20ExecuteMsg::Transfer {
+                    recipient: "bidder".to_string(),
+                    amount: Uint128::new(30),
+                })
+                .unwrap()
+        );
+        assert!(offers()
+            .may_load(deps.as_ref().storage, 2)
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_auctions() {
+        let mut deps = setup();
+
+        let start = mock_env().block.time;
+        let at = |seconds: u64| {
+            let mut env = mock_env();
+            env.block.time = start.plus_seconds(seconds);
+            env
+        };
+        let new_auction = |token_id: &str, kind: AuctionKind, end_time: Timestamp| {
+            ExecuteMsg::ReceiveNft(Cw721ReceiveMsg {
+                sender: String::from("seller"),
+                token_id: token_id.to_string(),
+                msg: to_json_binary(&ReceiveNftMsg::NewAuction {
+                    kind,
+                    end_time,
+                    denom: Some(Denom::Native("uxion".to_string())),
+                })
+                .unwrap(),
+            })
+        };
+        fn bid(
+            deps: DepsMut,
+            env: Env,
+            bidder: &str,
+            amount: u128,
+        ) -> Result<Response, ContractError> {
+            execute(
+                deps,
+                env,
+                mock_info(bidder, &coins(amount, "uxion")),
+                ExecuteMsg::PlaceBid { id: 1 },
+            )
+        }
+
+        for kind in [
+            AuctionKind::English {
+                reserve_price: Uint128::zero(),
+                min_increment: Uint128::new(10),
+                extension: 600,
+                max_extension: 900,
+            },
+            AuctionKind::English {
+                reserve_price: Uint128::new(100),
+                min_increment: Uint128::new(10),
+                extension: 600,
+                max_extension: 300,
+            },
+            AuctionKind::Dutch {
+                start_price: Uint128::new(1_000),
+                end_price: Uint128::zero(),
+            },
+        ] {
+            let err = execute(
+                deps.as_mut(),
+                at(0),
+                mock_info("cw404", &[]),
+                new_auction("1", kind, start.plus_seconds(3_600)),
+            )
+            .unwrap_err();
+            assert!(matches!(err, ContractError::InvalidAuction {}));
+        }
+
+        // English: reserve 100, steps of 10, late bids extend by 10 minutes
+        // but never more than 15 minutes past the original end
+        let english = AuctionKind::English {
+            reserve_price: Uint128::new(100),
+            min_increment: Uint128::new(10),
+            extension: 600,
+            max_extension: 900,
+        };
+        execute(
+            deps.as_mut(),
+            at(0),
+            mock_info("cw404", &[]),
+            new_auction("1", english, start.plus_seconds(3_600)),
+        )
+        .unwrap();
+
+        let err = bid(deps.as_mut(), at(10), "alice", 90).unwrap_err();
+        assert!(matches!(err, ContractError::BidTooLow { .. }));
+        bid(deps.as_mut(), at(10), "alice", 100).unwrap();
+        bid(deps.as_mut(), at(20), "bob", 105).unwrap_err();
+        let res = bid(deps.as_mut(), at(3_500), "bob", 110).unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "alice".to_string(),
+                amount: coins(100, "uxion"),
+            })
+        );
+        let auction: AuctionResponse =
+            from_json(query(deps.as_ref(), at(3_500), QueryMsg::GetAuction { id: 1 }).unwrap())
+                .unwrap();
+        assert_eq!(auction.auction.end_time, start.plus_seconds(4_100));
+        assert_eq!(auction.min_bid, Uint128::new(120));
+
+        let cancel = ExecuteMsg::CancelAuction { id: 1 };
+        let err = execute(deps.as_mut(), at(3_500), mock_info("seller", &[]), cancel).unwrap_err();
+        assert!(matches!(err, ContractError::AuctionHasBids { id: 1 }));
+
+        // the extension stops at the cap
+        bid(deps.as_mut(), at(4_000), "alice", 120).unwrap();
+        let auction: AuctionResponse =
+            from_json(query(deps.as_ref(), at(4_000), QueryMsg::GetAuction { id: 1 }).unwrap())
+                .unwrap();
+        assert_eq!(auction.auction.end_time, start.plus_seconds(4_500));
+
+        let settle = ExecuteMsg::SettleAuction { id: 1 };
+        let err = execute(
+            deps.as_mut(),
+            at(4_100),
+            mock_info("anyone", &[]),
+            settle.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::AuctionNotEnded { id: 1 }));
+        let res = execute(deps.as_mut(), at(4_500), mock_info("anyone", &[]), settle).unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            transfer_nft_msg(&Addr::unchecked("cw404"), &Addr::unchecked("alice"), "1").unwrap()
+        );
+        assert_eq!(
+            res.messages[1].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "seller".to_string(),
+                amount: coins(120, "uxion"),
+            })
+        );
+
+        // Dutch: 1000 down to 200 over 1000 seconds, so 600 halfway through
+        let dutch = AuctionKind::Dutch {
+            start_price: Uint128::new(1_000),
+            end_price: Uint128::new(200),
+        };
+        execute(
+            deps.as_mut(),
+            at(0),
+            mock_info("cw404", &[]),
+            new_auction("2", dutch, start.plus_seconds(1_000)),
+        )
+        .unwrap();
+        let res = execute(
+            deps.as_mut(),
+            at(500),
+            mock_info("alice", &coins(700, "uxion")),
+            ExecuteMsg::PlaceBid { id: 2 },
+        )
+        .unwrap();
+        let msgs: Vec<CosmosMsg> = res.messages.iter().map(|m| m.msg.clone()).collect();
+        assert_eq!(
+            msgs,
+            vec![
+                transfer_nft_msg(&Addr::unchecked("cw404"), &Addr::unchecked("alice"), "2")
+                    .unwrap(),
+                CosmosMsg::Bank(BankMsg::Send {
+                    to_address: "alice".to_string(),
+                    amount: coins(100, "uxion"),
+                }),
+                CosmosMsg::Bank(BankMsg::Send {
+                    to_address: "seller".to_string(),
+                    amount: coins(600, "uxion"),
+                }),
+            ]
+        );
+        assert!(AUCTIONS
+            .may_load(deps.as_ref().storage, 2)
+            .unwrap()
+            .is_none());
+
+        // an auction nobody bid on can be called off by its seller
+        let english = AuctionKind::English {
+            reserve_price: Uint128::new(100),
+            min_increment: Uint128::new(10),
+            extension: 600,
+            max_extension: 900,
+        };
+        execute(
+            deps.as_mut(),
+            at(0),
+            mock_info("cw404", &[]),
+            new_auction("3", english, start.plus_seconds(1_000)),
+        )
+        .unwrap();
+        let cancel = ExecuteMsg::CancelAuction { id: 3 };
+        let err = execute(
+            deps.as_mut(),
+            at(10),
+            mock_info("alice", &[]),
+            cancel.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        let res = execute(deps.as_mut(), at(10), mock_info("seller", &[]), cancel).unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            transfer_nft_msg(&Addr::unchecked("cw404"), &Addr::unchecked("seller"), "3").unwrap()
+        );
+        assert!(AUCTIONS
+            .may_load(deps.as_ref().storage, 3)
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_listing_expiry() {
+        let mut deps = setup();
+
+        let expires_at = mock_env().block.time.plus_seconds(100);
+        for token_id in ["1", "2"] {
+            let msg = ExecuteMsg::ReceiveNft(Cw721ReceiveMsg {
+                sender: String::from("seller"),
+                token_id: token_id.to_string(),
+                msg: to_json_binary(&ReceiveNftMsg::NewListing {
+                    price: Uint256::from(5u128),
+                    denom: Some(Denom::Native("uxion".to_string())),
+                    expires_at: (token_id == "1").then_some(expires_at),
+                })
+                .unwrap(),
+            });
+            execute(deps.as_mut(), mock_env(), mock_info("cw404", &[]), msg).unwrap();
+        }
+
+        let update = ExecuteMsg::UpdatePrice {
+            collection: "cw404".to_string(),
+            id: "1".to_string(),
+            price: Uint256::from(8u128),
+        };
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("buyer", &[]),
+            update.clone(),
+        )
+        .unwrap_err();
+        execute(deps.as_mut(), mock_env(), mock_info("seller", &[]), update).unwrap();
+        let listing: Listing = from_json(
+            query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::GetListing {
+                    collection: "cw404".to_string(),
+                    id: "1".to_string(),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(listing.price, Uint256::from(8u128));
+
+        let mut env = mock_env();
+        env.block.time = expires_at;
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("buyer", &coins(8, "uxion")),
+            ExecuteMsg::Buy {
+                collection: "cw404".to_string(),
+                id: "1".to_string(),
+            },
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::ListingExpired { .. }));
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("seller", &[]),
+            ExecuteMsg::UpdatePrice {
+                collection: "cw404".to_string(),
                 id: "1".to_string(),
+                price: Uint256::from(9u128),
+            },
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::ListingExpired { .. }));
+
+        // only the expired listing goes back to the seller
+        let res = execute(
+            deps.as_mut(),
+            env,
+            mock_info("anyone", &[]),
+            ExecuteMsg::CleanupExpired { limit: None },
+        )
+        .unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            transfer_nft_msg(&Addr::unchecked("cw404"), &Addr::unchecked("seller"), "1").unwrap()
+        );
+        assert_eq!(res.messages.len(), 1);
+        assert_eq!(get_listing_count(deps.as_ref()).unwrap(), 1);
+    }
+
+    #[test]
+    fn test_multiple_collections() {
+        let mut deps = setup();
+
+        let list = |price: u128| {
+            ExecuteMsg::ReceiveNft(Cw721ReceiveMsg {
+                sender: String::from("seller"),
+                token_id: "1".to_string(),
+                msg: to_json_binary(&ReceiveNftMsg::NewListing {
+                    price: Uint256::from(price),
+                    denom: None,
+                    expires_at: None,
+                })
+                .unwrap(),
+            })
+        };
+        let update = |open, add_collections| ExecuteMsg::UpdateConfig {
+            fee_bps: None,
+            fee_collector: None,
+            add_cw20s: None,
+            remove_cw20s: None,
+            add_natives: None,
+            remove_natives: None,
+            open,
+            add_collections,
+            remove_collections: None,
+        };
+
+        let err = execute(deps.as_mut(), mock_env(), mock_info("other", &[]), list(7)).unwrap_err();
+        assert!(matches!(err, ContractError::CollectionNotAccepted { .. }));
+
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            update(None, Some(vec!["other".to_string()])),
+        )
+        .unwrap();
+        // the same token id in two collections are two listings
+        execute(deps.as_mut(), mock_env(), mock_info("cw404", &[]), list(5)).unwrap();
+        execute(deps.as_mut(), mock_env(), mock_info("other", &[]), list(7)).unwrap();
+
+        let listings: Vec<Listing> = from_json(
+            query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::GetCollectionListings {
+                    collection: "other".to_string(),
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(listings.len(), 1);
+        assert_eq!(listings[0].collection, Addr::unchecked("other"));
+        assert_eq!(listings[0].price, Uint256::from(7u128));
+
+        let collections: CollectionsResponse = from_json(
+            query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::Collections {
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            collections,
+            CollectionsResponse {
+                open: false,
+                collections: vec![Addr::unchecked("cw404"), Addr::unchecked("other")],
+            }
+        );
+
+        // open mode takes tokens from any collection
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            update(Some(true), None),
+        )
+        .unwrap();
+        execute(deps.as_mut(), mock_env(), mock_info("third", &[]), list(9)).unwrap();
+        assert_eq!(get_listing_count(deps.as_ref()).unwrap(), 3);
+    }
+
+    #[test]
+    fn test_migrate() {
+        let mut deps = setup();
+        let migrate_msg = |limit| MigrateMsg {
+            owner: None,
+            accepted_natives: None,
+            limit: Some(limit),
+        };
+
+        set_contract_version(deps.as_mut().storage, "other", "0.1.0").unwrap();
+        let err = migrate(deps.as_mut(), mock_env(), migrate_msg(10)).unwrap_err();
+        assert!(matches!(err, ContractError::CannotMigrate { .. }));
+
+        // listings of the first release were keyed by token id alone
+        set_contract_version(deps.as_mut().storage, CONTRACT_NAME, "0.1.0").unwrap();
+        let legacy: Map<String, LegacyListing> = Map::new("listings");
+        for (id, denom) in [
+            ("1", None),
+            ("2", Some(Denom::Native("uxion".to_string()))),
+            ("3", None),
+        ] {
+            let listing = LegacyListing {
+                nft_id: id.to_string(),
+                price: Uint256::from(5u128),
+                owner: Addr::unchecked("seller"),
+                denom,
+                expires_at: None,
+            };
+            legacy
+                .save(deps.as_mut().storage, id.to_string(), &listing)
+                .unwrap();
+        }
+
+        let res = migrate(deps.as_mut(), mock_env(), migrate_msg(2)).unwrap();
+        assert!(res
+            .attributes
+            .iter()
+            .any(|a| a.key == "migrated" && a.value == "2"));
+        assert!(res
+            .attributes
+            .iter()
+            .any(|a| a.key == "complete" && a.value == "false"));
+        let res = migrate(deps.as_mut(), mock_env(), migrate_msg(2)).unwrap();
+        assert!(res
+            .attributes
+            .iter()
+            .any(|a| a.key == "complete" && a.value == "true"));
+        assert_eq!(
+            get_contract_version(deps.as_ref().storage).unwrap().version,
+            CONTRACT_VERSION
+        );
+
+        assert!(legacy.is_empty(deps.as_ref().storage));
+        let listing = listings()
+            .load(
+                deps.as_ref().storage,
+                (Addr::unchecked("cw404"), "1".to_string()),
+            )
+            .unwrap();
+        assert_eq!(listing.collection, Addr::unchecked("cw404"));
+        assert_eq!(listing.denom, Denom::Cw20(Addr::unchecked("cw20")));
+        let by_seller = listings()
+            .idx
+            .seller
+            .prefix(Addr::unchecked("seller"))
+            .keys(deps.as_ref().storage, None, None, Order::Ascending)
+            .count();
+        assert_eq!(by_seller, 3);
+        let floor = get_floor_price(
+            deps.as_ref(),
+            mock_env(),
+            "cw404".to_string(),
+            Denom::Native("uxion".to_string()),
+        )
+        .unwrap();
+        assert_eq!(floor.unwrap().nft_id, "2");
+    }
+
+    #[test]
+    fn test_listings_by_price() {
+        let mut deps = setup();
+
+        let expires_at = mock_env().block.time.plus_seconds(100);
+        for (token_id, price, seller) in [
+            ("1", 300u128, "alice"),
+            ("2", 100, "bob"),
+            ("3", 200, "alice"),
+            ("4", 1_000, "bob"),
+            ("5", 50, "alice"),
+        ] {
+            let msg = ExecuteMsg::ReceiveNft(Cw721ReceiveMsg {
+                sender: seller.to_string(),
+                token_id: token_id.to_string(),
+                msg: to_json_binary(&ReceiveNftMsg::NewListing {
+                    price: Uint256::from(price),
+                    denom: None,
+                    expires_at: (token_id == "5").then_some(expires_at),
+                })
+                .unwrap(),
+            });
+            execute(deps.as_mut(), mock_env(), mock_info("cw404", &[]), msg).unwrap();
+        }
+
+        // priced in another denom, so it never shows up below
+        let msg = ExecuteMsg::ReceiveNft(Cw721ReceiveMsg {
+            sender: "bob".to_string(),
+            token_id: "6".to_string(),
+            msg: to_json_binary(&ReceiveNftMsg::NewListing {
+                price: Uint256::from(150u128),
+                denom: Some(Denom::Native("uxion".to_string())),
+                expires_at: None,
+            })
+            .unwrap(),
+        });
+        execute(deps.as_mut(), mock_env(), mock_info("cw404", &[]), msg).unwrap();
+
+        let by_price = |start_after: Option<&str>, order| -> Vec<String> {
+            let listings: Vec<Listing> = from_json(
+                query(
+                    deps.as_ref(),
+                    mock_env(),
+                    QueryMsg::ListingsByPrice {
+                        collection: "cw404".to_string(),
+                        denom: Denom::Cw20(Addr::unchecked("cw20")),
+                        min: Some(Uint256::from(100u128)),
+                        max: Some(Uint256::from(300u128)),
+                        start_after: start_after.map(str::to_string),
+                        limit: Some(2),
+                        order: Some(order),
+                    },
+                )
+                .unwrap(),
+            )
+            .unwrap();
+            listings.into_iter().map(|listing| listing.nft_id).collect()
+        };
+        assert_eq!(by_price(None, SortOrder::Ascending), vec!["2", "3"]);
+        assert_eq!(by_price(Some("3"), SortOrder::Ascending), vec!["1"]);
+        assert_eq!(by_price(None, SortOrder::Descending), vec!["1", "3"]);
+        assert_eq!(by_price(Some("3"), SortOrder::Descending), vec!["2"]);
+
+        let floor = |env| -> Option<Listing> {
+            from_json(
+                query(
+                    deps.as_ref(),
+                    env,
+                    QueryMsg::FloorPrice {
+                        collection: "cw404".to_string(),
+                        denom: Denom::Cw20(Addr::unchecked("cw20")),
+                    },
+                )
+                .unwrap(),
+            )
+            .unwrap()
+        };
+        assert_eq!(floor(mock_env()).unwrap().nft_id, "5");
+        // an expired listing is no longer the floor
+        let mut env = mock_env();
+        env.block.time = expires_at;
+        assert_eq!(floor(env).unwrap().nft_id, "2");
+
+        let seller_page = |start_after: Option<(String, String)>| -> Vec<String> {
+            let listings: Vec<Listing> = from_json(
+                query(
+                    deps.as_ref(),
+                    mock_env(),
+                    QueryMsg::GetListingsBySeller {
+                        seller: "alice".to_string(),
+                        start_after,
+                        limit: Some(2),
+                    },
+                )
+                .unwrap(),
+            )
+            .unwrap();
+            listings.into_iter().map(|listing| listing.nft_id).collect()
+        };
+        assert_eq!(seller_page(None), vec!["1", "3"]);
+        assert_eq!(
+            seller_page(Some(("cw404".to_string(), "3".to_string()))),
+            vec!["5"]
+        );
+    }
+
+    #[test]
+    fn test_sales_history_and_stats() {
+        let mut deps = setup();
+
+        set_fee(deps.as_mut(), 100);
+
+        for (token_id, price, denom) in [
+            ("1", 100u128, Some(Denom::Native("uxion".to_string()))),
+            ("2", 50, Some(Denom::Native("uxion".to_string()))),
+            ("3", 30, None),
+        ] {
+            let msg = ExecuteMsg::ReceiveNft(Cw721ReceiveMsg {
+                sender: String::from("seller"),
+                token_id: token_id.to_string(),
+                msg: to_json_binary(&ReceiveNftMsg::NewListing {
+                    price: Uint256::from(price),
+                    denom,
+                    expires_at: None,
+                })
+                .unwrap(),
+            });
+            execute(deps.as_mut(), mock_env(), mock_info("cw404", &[]), msg).unwrap();
+        }
+
+        let buy = |id: &str| ExecuteMsg::Buy {
+            collection: "cw404".to_string(),
+            id: id.to_string(),
+        };
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("buyer", &coins(100, "uxion")),
+            buy("1"),
+        )
+        .unwrap();
+        // two days later
+        let mut later = mock_env();
+        later.block.time = later.block.time.plus_seconds(2 * 24 * 60 * 60);
+        execute(
+            deps.as_mut(),
+            later.clone(),
+            mock_info("buyer", &coins(50, "uxion")),
+            buy("2"),
+        )
+        .unwrap();
+        let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: String::from("buyer"),
+            amount: Uint128::new(30),
+            msg: to_json_binary(&ReceiveMsg::Buy {
+                collection: "cw404".to_string(),
+                id: "3".to_string(),
+            })
+            .unwrap(),
+        });
+        execute(deps.as_mut(), later.clone(), mock_info("cw20", &[]), msg).unwrap();
+
+        let history: Vec<Sale> = from_json(
+            query(
+                deps.as_ref(),
+                later.clone(),
+                QueryMsg::SalesHistory {
+                    collection: "cw404".to_string(),
+                    token_id: None,
+                    start_after: Some(3),
+                    limit: None,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            history.iter().map(|sale| sale.id).collect::<Vec<_>>(),
+            vec![2, 1]
+        );
+        assert_eq!(
+            history[1],
+            Sale {
+                id: 1,
+                collection: Addr::unchecked("cw404"),
+                token_id: "1".to_string(),
+                seller: Addr::unchecked("seller"),
+                buyer: Addr::unchecked("buyer"),
+                price: Uint128::new(100),
+                denom: Denom::Native("uxion".to_string()),
+                royalty: Uint128::zero(),
+                fee: Uint128::new(1),
+                time: mock_env().block.time,
+            }
+        );
+
+        let stats: CollectionStatsResponse = from_json(
+            query(
+                deps.as_ref(),
+                later,
+                QueryMsg::CollectionStats {
+                    collection: "cw404".to_string(),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        let cw20 = |amount| Volume {
+            denom: Denom::Cw20(Addr::unchecked("cw20")),
+            amount: Uint128::new(amount),
+        };
+        let uxion = |amount| Volume {
+            denom: Denom::Native("uxion".to_string()),
+            amount: Uint128::new(amount),
+        };
+        assert_eq!(stats.sale_count, 3);
+        assert_eq!(stats.volume, vec![cw20(30), uxion(150)]);
+        assert_eq!(stats.last_sale.unwrap().token_id, "3");
+        assert_eq!(stats.sale_count_24h, 2);
+        assert_eq!(stats.volume_24h, vec![cw20(30), uxion(50)]);
+    }
+
+    #[test]
+    fn test_buy_many_and_sweep_floor() {
+        let mut deps = setup();
+
+        let uxion = Some(Denom::Native("uxion".to_string()));
+        for (token_id, price, denom) in [
+            ("1", 100u128, uxion.clone()),
+            ("2", 50, uxion.clone()),
+            ("3", 200, uxion),
+            ("4", 80, None),
+            ("5", 40, None),
+            ("6", 90, None),
+        ] {
+            let msg = ExecuteMsg::ReceiveNft(Cw721ReceiveMsg {
+                sender: String::from("seller"),
+                token_id: token_id.to_string(),
+                msg: to_json_binary(&ReceiveNftMsg::NewListing {
+                    price: Uint256::from(price),
+                    denom,
+                    expires_at: None,
+                })
+                .unwrap(),
+            });
+            execute(deps.as_mut(), mock_env(), mock_info("cw404", &[]), msg).unwrap();
+        }
+
+        // 3 is over what is left of max_total, 4 is priced in the cw20, 9
+        // is not listed and the second 2 was already bought
+        let ids = ["2", "1", "3", "4", "9", "2"]
+            .iter()
+            .map(|id| ("cw404".to_string(), id.to_string()))
+            .collect();
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("buyer", &coins(300, "uxion")),
+            ExecuteMsg::BuyMany {
+                ids,
+                max_total: Uint128::new(170),
+            },
+        )
+        .unwrap();
+        assert!(res
+            .attributes
+            .iter()
+            .any(|a| a.key == "spent" && a.value == "150"));
+        assert_eq!(
+            res.messages.last().unwrap().msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "buyer".to_string(),
+                amount: coins(150, "uxion"),
+            })
+        );
+        assert_eq!(get_listing_count(deps.as_ref()).unwrap(), 4);
+
+        // the two cheapest cw20 listings under 85 each
+        let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: String::from("buyer"),
+            amount: Uint128::new(200),
+            msg: to_json_binary(&ReceiveMsg::SweepFloor {
+                collection: "cw404".to_string(),
+                count: 3,
+                max_price_each: Uint128::new(85),
             })
             .unwrap(),
         });
+        let res = execute(deps.as_mut(), mock_env(), mock_info("cw20", &[]), msg).unwrap();
+        let bought: Vec<&str> = res
+            .attributes
+            .iter()
+            .filter(|a| a.key == "NFT")
+            .map(|a| a.value.as_str())
+            .collect();
+        assert_eq!(bought, vec!["5", "4"]);
+        assert_eq!(
+            res.messages.last().unwrap().msg,
+            Cw20Contract(Addr::unchecked("cw20"))
+                .call(Cw20ExecuteMsg::Transfer {
+                    recipient: "buyer".to_string(),
+                    amount: Uint128::new(80),
+                })
+                .unwrap()
+        );
 
-        info.sender = Addr::unchecked("cw20");
-        let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        // nothing left under the cap
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("buyer", &coins(100, "uxion")),
+            ExecuteMsg::SweepFloor {
+                collection: "cw404".to_string(),
+                count: 1,
+                max_price_each: Uint128::new(100),
+            },
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::NothingBought {}));
     }
 }
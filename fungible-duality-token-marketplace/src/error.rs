@@ -1,4 +1,6 @@
-use cosmwasm_std::{StdError, Uint256};
+use cosmwasm_std::{OverflowError, StdError, Uint128, Uint256};
+use cw_ownable::OwnershipError;
+use cw_utils::PaymentError;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -6,12 +8,75 @@ pub enum ContractError {
     #[error("{0}")]
     Std(#[from] StdError),
 
+    #[error("{0}")]
+    Payment(#[from] PaymentError),
+
+    #[error("{0}")]
+    Overflow(#[from] OverflowError),
+
+    #[error("{0}")]
+    SemVer(#[from] semver::Error),
+
+    #[error("{0}")]
+    Ownership(#[from] OwnershipError),
+
     #[error("Unauthorized")]
     Unauthorized {},
 
+    #[error("Cannot migrate from {contract} {version}")]
+    CannotMigrate { contract: String, version: String },
+
     #[error("Payment is not the same as the price {price}")]
     IncorrectPayment { price: Uint256 },
 
+    #[error("Fee of {bps} bps exceeds the maximum of {max}")]
+    FeeTooHigh { bps: u16, max: u16 },
+
+    #[error("A protocol fee requires a fee collector")]
+    MissingFeeCollector {},
+
+    #[error("Listing is priced in a different denom")]
+    IncorrectDenom {},
+
+    #[error("Token {address} is not accepted for payment")]
+    Cw20NotAccepted { address: String },
+
+    #[error("Denom {denom} is not accepted for payment")]
+    NativeNotAccepted { denom: String },
+
+    #[error("Expiration is already in the past")]
+    InvalidExpiration {},
+
+    #[error("Offer {id} has expired")]
+    OfferExpired { id: u64 },
+
+    #[error("Offer {id} is for a different token")]
+    OfferTokenMismatch { id: u64 },
+
+    #[error("Listing {id} has expired")]
+    ListingExpired { id: String },
+
+    #[error("Invalid auction parameters")]
+    InvalidAuction {},
+
+    #[error("Auction {id} has ended")]
+    AuctionEnded { id: u64 },
+
+    #[error("Auction {id} has not ended yet")]
+    AuctionNotEnded { id: u64 },
+
+    #[error("Auction {id} already has bids")]
+    AuctionHasBids { id: u64 },
+
+    #[error("Bid must be at least {min}")]
+    BidTooLow { min: Uint128 },
+
+    #[error("Collection {address} is not accepted")]
+    CollectionNotAccepted { address: String },
+
+    #[error("No listing could be bought within the given bounds")]
+    NothingBought {},
+
     #[error("The reply ID is unrecognized")]
     UnrecognizedReply {},
 }
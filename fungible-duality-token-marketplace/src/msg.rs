@@ -1,51 +1,323 @@
-use crate::state::Listing;
+use crate::state::{Auction, AuctionKind, Listing, Offer, RoyaltyTotal, Sale, Volume};
 use cosmwasm_schema::{cw_serde, QueryResponses};
-use cosmwasm_std::Uint256;
-use cw20::Cw20ReceiveMsg;
+use cosmwasm_std::{Addr, Order, Timestamp, Uint128, Uint256};
+use cw20::{Cw20ReceiveMsg, Denom};
 use cw721::Cw721ReceiveMsg;
+use cw_ownable::{cw_ownable_execute, cw_ownable_query};
+use cw_utils::Expiration;
 
 #[cw_serde]
 pub struct InstantiateMsg {
     pub cw404_address: String,
     pub cw20_address: String,
+    pub fee_bps: u16,
+    pub fee_collector: Option<String>,
+    /// Native denoms accepted for payment, none when unset
+    pub accepted_natives: Option<Vec<String>>,
 }
 
+#[cw_ownable_execute]
 #[cw_serde]
 pub enum ExecuteMsg {
-    CancelListing { id: String },
+    CancelListing {
+        collection: String,
+        id: String,
+    },
+    /// Buys a listing priced in a native denom with the attached funds
+    Buy {
+        collection: String,
+        id: String,
+    },
+    /// Seller only, the denom stays the same. Expired listings can only be
+    /// cancelled or cleaned up.
+    UpdatePrice {
+        collection: String,
+        id: String,
+        price: Uint256,
+    },
+    /// Returns up to `limit` expired listings to their sellers. Anyone can
+    /// call it.
+    CleanupExpired {
+        limit: Option<u32>,
+    },
     Receive(Cw20ReceiveMsg),
     ReceiveNft(Cw721ReceiveMsg),
+    /// Escrows the attached native funds as an offer on `nft_id`, or on any
+    /// token of the collection when `nft_id` is `None`
+    MakeOffer {
+        collection: String,
+        nft_id: Option<String>,
+        price: Uint128,
+        expires: Option<Expiration>,
+    },
+    /// Refunds the offer. Only the bidder can cancel until it expires,
+    /// after that anyone can.
+    CancelOffer {
+        id: u64,
+    },
+    /// Bids on an auction priced in a native denom with the attached funds
+    PlaceBid {
+        id: u64,
+    },
+    /// Buys whichever of the (collection, token id) listings are still
+    /// available and priced in the attached denom, in order, while the total
+    /// stays within `max_total` and the payment. The rest is refunded.
+    BuyMany {
+        ids: Vec<(String, String)>,
+        max_total: Uint128,
+    },
+    /// Buys up to `count` of the cheapest listings of `collection` priced in
+    /// the attached denom at no more than `max_price_each`, refunding the
+    /// rest of the payment.
+    SweepFloor {
+        collection: String,
+        count: u32,
+        max_price_each: Uint128,
+    },
+    /// Hands the token to the winner, or back to the seller if nobody won.
+    /// Anyone can settle once the auction has ended.
+    SettleAuction {
+        id: u64,
+    },
+    /// Seller only. Takes the token back from an auction nobody has bid on.
+    CancelAuction {
+        id: u64,
+    },
+    /// Owner only. Only fields that are set get updated.
+    UpdateConfig {
+        fee_bps: Option<u16>,
+        fee_collector: Option<String>,
+        add_cw20s: Option<Vec<String>>,
+        remove_cw20s: Option<Vec<String>>,
+        add_natives: Option<Vec<String>>,
+        remove_natives: Option<Vec<String>>,
+        open: Option<bool>,
+        add_collections: Option<Vec<String>>,
+        remove_collections: Option<Vec<String>>,
+    },
 }
 
 #[cw_serde]
 pub enum ReceiveMsg {
-    Buy { id: String },
+    Buy {
+        collection: String,
+        id: String,
+    },
+    MakeOffer {
+        collection: String,
+        nft_id: Option<String>,
+        price: Uint128,
+        expires: Option<Expiration>,
+    },
+    PlaceBid {
+        id: u64,
+    },
+    BuyMany {
+        ids: Vec<(String, String)>,
+        max_total: Uint128,
+    },
+    SweepFloor {
+        collection: String,
+        count: u32,
+        max_price_each: Uint128,
+    },
 }
 
 #[cw_serde]
 pub enum ReceiveNftMsg {
-    NewListing { price: Uint256 },
+    NewListing {
+        price: Uint256,
+        /// Defaults to the configured cw20
+        denom: Option<Denom>,
+        expires_at: Option<Timestamp>,
+    },
+    /// Sells the sent token to the bidder of offer `id`
+    AcceptOffer { id: u64 },
+    NewAuction {
+        kind: AuctionKind,
+        end_time: Timestamp,
+        /// Defaults to the configured cw20
+        denom: Option<Denom>,
+    },
 }
 
+#[cw_ownable_query]
 #[cw_serde]
 #[derive(QueryResponses)]
 pub enum QueryMsg {
+    #[returns(ConfigResponse)]
+    Config {},
     #[returns(Listing)]
-    GetListing { id: String },
+    GetListing { collection: String, id: String },
+    /// Listings of one collection, ordered by token id
+    #[returns(Vec<Listing>)]
+    GetCollectionListings {
+        collection: String,
+        start_after: Option<String>,
+        limit: Option<u64>,
+    },
     #[returns(Vec<Listing>)]
     GetListingsBySeller {
         seller: String,
-        from_index: Option<u64>,
+        /// (collection, token id) of the last listing of the previous page
+        start_after: Option<(String, String)>,
         limit: Option<u64>,
     },
     #[returns(Vec<Listing>)]
     GetAllListings {
-        from_index: Option<u64>,
+        start_after: Option<(String, String)>,
+        limit: Option<u64>,
+    },
+    /// Listings of `collection` priced in `denom` between `min` and `max`
+    /// inclusive, sorted by price. `start_after` is the token id of the last
+    /// listing of the previous page.
+    #[returns(Vec<Listing>)]
+    ListingsByPrice {
+        collection: String,
+        denom: Denom,
+        min: Option<Uint256>,
+        max: Option<Uint256>,
+        start_after: Option<String>,
         limit: Option<u64>,
+        order: Option<SortOrder>,
     },
+    /// The cheapest listing of `collection` priced in `denom` that can
+    /// still be bought
+    #[returns(Option<Listing>)]
+    FloorPrice { collection: String, denom: Denom },
     #[returns(u128)]
     GetListingCount {},
+    /// Royalties paid out on sales of `collection`, per denom
+    #[returns(Vec<RoyaltyTotal>)]
+    RoyaltiesPaid { collection: String },
+    #[returns(Offer)]
+    GetOffer { id: u64 },
+    /// Offers on one token, not including collection offers
+    #[returns(Vec<Offer>)]
+    GetOffersByNft {
+        collection: String,
+        nft_id: String,
+        start_after: Option<u64>,
+        limit: Option<u64>,
+    },
+    #[returns(Vec<Offer>)]
+    GetCollectionOffers {
+        collection: String,
+        start_after: Option<u64>,
+        limit: Option<u64>,
+    },
+    #[returns(Vec<Offer>)]
+    GetOffersByBidder {
+        bidder: String,
+        start_after: Option<u64>,
+        limit: Option<u64>,
+    },
+    /// Newest first, optionally for a single token
+    #[returns(Vec<Sale>)]
+    SalesHistory {
+        collection: String,
+        token_id: Option<String>,
+        start_after: Option<u64>,
+        limit: Option<u64>,
+    },
+    #[returns(CollectionStatsResponse)]
+    CollectionStats { collection: String },
+    #[returns(CollectionsResponse)]
+    Collections {
+        start_after: Option<String>,
+        limit: Option<u64>,
+    },
+    #[returns(AuctionResponse)]
+    GetAuction { id: u64 },
+    #[returns(Vec<Auction>)]
+    GetAllAuctions {
+        start_after: Option<u64>,
+        limit: Option<u64>,
+    },
+}
+
+/// The cw2981 queries the marketplace sends to a listed collection, as
+/// implemented by cw2981-royalties
+#[cw_serde]
+pub enum Cw2981QueryMsg {
+    RoyaltyInfo {
+        token_id: String,
+        sale_price: Uint128,
+    },
+    CheckRoyalties {},
+}
+
+#[cw_serde]
+pub struct RoyaltiesInfoResponse {
+    pub address: String,
+    pub royalty_amount: Uint128,
+}
+
+#[cw_serde]
+pub struct CheckRoyaltiesResponse {
+    pub royalty_payments: bool,
+}
+
+#[cw_serde]
+pub enum SortOrder {
+    Ascending,
+    Descending,
+}
+
+impl From<SortOrder> for Order {
+    fn from(order: SortOrder) -> Order {
+        match order {
+            SortOrder::Ascending => Order::Ascending,
+            SortOrder::Descending => Order::Descending,
+        }
+    }
+}
+
+#[cw_serde]
+pub struct AuctionResponse {
+    pub auction: Auction,
+    /// The least a bid must offer right now, the current price of a Dutch
+    /// auction
+    pub min_bid: Uint128,
+    pub ended: bool,
+}
+
+#[cw_serde]
+pub struct CollectionStatsResponse {
+    pub sale_count: u64,
+    /// All time volume per denom
+    pub volume: Vec<Volume>,
+    pub last_sale: Option<Sale>,
+    /// Sales in the 24 hours up to the queried block
+    pub sale_count_24h: u64,
+    pub volume_24h: Vec<Volume>,
+}
+
+#[cw_serde]
+pub struct CollectionsResponse {
+    /// Whether collections outside the allowlist are accepted too
+    pub open: bool,
+    pub collections: Vec<Addr>,
 }
 
 #[cw_serde]
-pub struct MigrateMsg {}
+pub struct ConfigResponse {
+    pub cw404_address: Addr,
+    pub cw20_address: Addr,
+    pub fee_bps: u16,
+    pub fee_collector: Option<Addr>,
+    pub accepted_cw20s: Vec<Addr>,
+    pub accepted_natives: Vec<String>,
+    pub open: bool,
+}
+
+#[cw_serde]
+pub struct MigrateMsg {
+    /// Owner to set on contracts deployed before ownership existed
+    pub owner: Option<String>,
+    /// Native denoms to accept from now on. Earlier versions took any.
+    pub accepted_natives: Option<Vec<String>>,
+    /// Most legacy listings to re-key in this migration, run it again with
+    /// the same code until `complete` is reported
+    pub limit: Option<u32>,
+}
@@ -1,20 +1,269 @@
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{Addr, Uint256};
-use cw_storage_plus::{Item, Map};
+use cosmwasm_std::{Addr, Empty, Timestamp, Uint128, Uint256};
+use cw20::Denom;
+use cw_storage_plus::{Index, IndexList, IndexedMap, Item, Map, MultiIndex};
+use cw_utils::Expiration;
 
 #[cw_serde]
 pub struct Config {
+    /// The collection the marketplace was deployed for, always allowed
     pub cw404_address: Addr,
     pub cw20_address: Addr,
+    /// Protocol fee taken from every sale, in basis points
+    #[serde(default)]
+    pub fee_bps: u16,
+    /// Receives the protocol fee; required while `fee_bps` is non-zero
+    #[serde(default)]
+    pub fee_collector: Option<Addr>,
+    /// Accept tokens from any cw721, not just allowlisted collections
+    #[serde(default)]
+    pub open: bool,
 }
 
 #[cw_serde]
 pub struct Listing {
+    pub collection: Addr,
     pub nft_id: String,
     pub price: Uint256,
     pub owner: Addr,
+    /// What the buyer pays in, a native denom or an accepted cw20
+    pub denom: Denom,
+    /// The listing can no longer be bought from this time on
+    #[serde(default)]
+    pub expires_at: Option<Timestamp>,
 }
 
 pub const CONFIG: Item<Config> = Item::new("config");
-pub const LISTINGS: Map<String, Listing> = Map::new("listings");
+
+/// (collection, denom key, big-endian price bytes)
+pub type PriceKey = (Addr, String, Vec<u8>);
+
+pub struct ListingIndexes<'a> {
+    pub seller: MultiIndex<'a, Addr, Listing, (Addr, String)>,
+    /// Big-endian price bytes per collection and denom, so they sort
+    /// numerically and amounts of different denoms are never mixed
+    pub price: MultiIndex<'a, PriceKey, Listing, (Addr, String)>,
+    /// Expiry in nanoseconds, `u64::MAX` for listings that never expire
+    pub expires_at: MultiIndex<'a, u64, Listing, (Addr, String)>,
+}
+
+impl<'a> IndexList<Listing> for ListingIndexes<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<Listing>> + '_> {
+        let v: Vec<&dyn Index<Listing>> = vec![&self.seller, &self.price, &self.expires_at];
+        Box::new(v.into_iter())
+    }
+}
+
+/// Keyed by (collection, token id)
+pub fn listings<'a>() -> IndexedMap<'a, (Addr, String), Listing, ListingIndexes<'a>> {
+    let indexes = ListingIndexes {
+        seller: MultiIndex::new(
+            |_pk, listing| listing.owner.clone(),
+            "collection_listings",
+            "collection_listings__seller",
+        ),
+        price: MultiIndex::new(
+            |_pk, listing| {
+                (
+                    listing.collection.clone(),
+                    denom_key(&listing.denom),
+                    listing.price.to_be_bytes().to_vec(),
+                )
+            },
+            "collection_listings",
+            "collection_listings__price",
+        ),
+        expires_at: MultiIndex::new(
+            |_pk, listing| listing.expires_at.map_or(u64::MAX, |time| time.nanos()),
+            "collection_listings",
+            "collection_listings__expires_at",
+        ),
+    };
+    IndexedMap::new("collection_listings", indexes)
+}
+/// A storage key for `denom`: the native denom or the cw20 address
+pub fn denom_key(denom: &Denom) -> String {
+    match denom {
+        Denom::Native(denom) => denom.clone(),
+        Denom::Cw20(address) => address.to_string(),
+    }
+}
+
 pub const LISTING_COUNTER: Item<u128> = Item::new("listing_counter");
+#[cw_serde]
+pub struct RoyaltyTotal {
+    pub denom: Denom,
+    pub amount: Uint128,
+}
+
+/// Royalties paid out per collection, keyed by the native denom or cw20
+/// address they were paid in
+pub const ROYALTIES_PAID: Map<(Addr, String), RoyaltyTotal> = Map::new("royalties_paid");
+
+/// cw20 tokens listings may be priced in, seeded with `Config.cw20_address`
+pub const ACCEPTED_CW20S: Map<Addr, Empty> = Map::new("accepted_cw20s");
+
+/// Native denoms listings, auctions and offers may be priced in
+pub const ACCEPTED_NATIVES: Map<String, Empty> = Map::new("accepted_natives");
+
+/// Collections that may list, seeded with `Config.cw404_address`
+pub const COLLECTIONS: Map<Addr, Empty> = Map::new("collections");
+
+/// A bid escrowed in the marketplace until it is accepted or cancelled
+#[cw_serde]
+pub struct Offer {
+    pub id: u64,
+    pub bidder: Addr,
+    pub collection: Addr,
+    /// `None` for a collection offer that any holder can fill
+    pub nft_id: Option<String>,
+    pub price: Uint128,
+    pub denom: Denom,
+    pub expires: Expiration,
+}
+
+pub struct OfferIndexes<'a> {
+    pub bidder: MultiIndex<'a, Addr, Offer, u64>,
+    /// Collection offers are indexed under an empty token id
+    pub nft: MultiIndex<'a, (Addr, String), Offer, u64>,
+}
+
+impl<'a> IndexList<Offer> for OfferIndexes<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<Offer>> + '_> {
+        let v: Vec<&dyn Index<Offer>> = vec![&self.bidder, &self.nft];
+        Box::new(v.into_iter())
+    }
+}
+
+pub fn offers<'a>() -> IndexedMap<'a, u64, Offer, OfferIndexes<'a>> {
+    let indexes = OfferIndexes {
+        bidder: MultiIndex::new(
+            |_pk, offer| offer.bidder.clone(),
+            "offers",
+            "offers__bidder",
+        ),
+        nft: MultiIndex::new(
+            |_pk, offer| {
+                (
+                    offer.collection.clone(),
+                    offer.nft_id.clone().unwrap_or_default(),
+                )
+            },
+            "offers",
+            "offers__nft",
+        ),
+    };
+    IndexedMap::new("offers", indexes)
+}
+
+pub const OFFER_COUNTER: Item<u64> = Item::new("offer_counter");
+
+#[cw_serde]
+pub enum AuctionKind {
+    /// Ascending bids, the highest bid above the reserve wins at the end
+    English {
+        reserve_price: Uint128,
+        min_increment: Uint128,
+        /// A bid placed closer than this many seconds to the end pushes the
+        /// end back to this far after the bid
+        extension: u64,
+        /// Late bids never push the end more than this many seconds past the
+        /// original end time
+        max_extension: u64,
+    },
+    /// The price falls linearly from `start_price` to `end_price` until the
+    /// end, the first bid at the current price wins
+    Dutch {
+        start_price: Uint128,
+        end_price: Uint128,
+    },
+}
+
+#[cw_serde]
+pub struct Bid {
+    pub bidder: Addr,
+    pub amount: Uint128,
+}
+
+#[cw_serde]
+pub struct Auction {
+    pub id: u64,
+    pub collection: Addr,
+    pub nft_id: String,
+    pub seller: Addr,
+    pub denom: Denom,
+    pub kind: AuctionKind,
+    pub start_time: Timestamp,
+    pub end_time: Timestamp,
+    /// Latest the end time can be extended to
+    pub max_end_time: Timestamp,
+    /// Escrowed highest bid of an English auction
+    pub highest_bid: Option<Bid>,
+}
+
+pub const AUCTIONS: Map<u64, Auction> = Map::new("auctions");
+pub const AUCTION_COUNTER: Item<u64> = Item::new("auction_counter");
+
+/// A completed sale, whichever way it happened
+#[cw_serde]
+pub struct Sale {
+    pub id: u64,
+    pub collection: Addr,
+    pub token_id: String,
+    pub seller: Addr,
+    pub buyer: Addr,
+    pub price: Uint128,
+    pub denom: Denom,
+    pub royalty: Uint128,
+    pub fee: Uint128,
+    pub time: Timestamp,
+}
+
+pub struct SaleIndexes<'a> {
+    pub collection: MultiIndex<'a, Addr, Sale, u64>,
+    pub token: MultiIndex<'a, (Addr, String), Sale, u64>,
+}
+
+impl<'a> IndexList<Sale> for SaleIndexes<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<Sale>> + '_> {
+        let v: Vec<&dyn Index<Sale>> = vec![&self.collection, &self.token];
+        Box::new(v.into_iter())
+    }
+}
+
+pub fn sales<'a>() -> IndexedMap<'a, u64, Sale, SaleIndexes<'a>> {
+    let indexes = SaleIndexes {
+        collection: MultiIndex::new(
+            |_pk, sale| sale.collection.clone(),
+            "sales",
+            "sales__collection",
+        ),
+        token: MultiIndex::new(
+            |_pk, sale| (sale.collection.clone(), sale.token_id.clone()),
+            "sales",
+            "sales__token",
+        ),
+    };
+    IndexedMap::new("sales", indexes)
+}
+
+pub const SALE_COUNTER: Item<u64> = Item::new("sale_counter");
+
+#[cw_serde]
+#[derive(Default)]
+pub struct CollectionStats {
+    pub sale_count: u64,
+    pub last_sale: Option<u64>,
+}
+
+#[cw_serde]
+pub struct Volume {
+    pub denom: Denom,
+    pub amount: Uint128,
+}
+
+pub const COLLECTION_STATS: Map<Addr, CollectionStats> = Map::new("collection_stats");
+
+/// All time volume per collection, keyed by the native denom or cw20
+/// address it was paid in
+pub const VOLUME: Map<(Addr, String), Volume> = Map::new("volume");
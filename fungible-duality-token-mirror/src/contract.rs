@@ -0,0 +1,493 @@
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::entry_point;
+use cosmwasm_std::{
+    to_json_binary, Addr, Binary, CosmosMsg, Deps, DepsMut, Env, Event, MessageInfo, Response,
+    StdError, StdResult, WasmMsg,
+};
+use cw2::{get_contract_version, set_contract_version};
+use cw20::TokenInfoResponse;
+use cw404::msg::{
+    ExecuteMsg as BaseExecuteMsg, MirrorMsg, NftExtension, QueryMsg as BaseQueryMsg,
+    SupplyStatsResponse,
+};
+use cw721::{
+    AllNftInfoResponse, Approval, ApprovalResponse, ApprovalsResponse, ContractInfoResponse,
+    Cw721ReceiveMsg, Expiration, NftInfoResponse, NumTokensResponse, OwnerOfResponse,
+    TokensResponse,
+};
+
+use crate::error::ContractError;
+use crate::msg::{ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg};
+use crate::state::BASE;
+use semver::Version;
+
+pub const CONTRACT_NAME: &str = "cw404-mirror";
+pub const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+const DEFAULT_LIMIT: u32 = 10;
+const MAX_LIMIT: u32 = 100;
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn instantiate(
+    deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    msg: InstantiateMsg,
+) -> Result<Response, ContractError> {
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    let base = deps.api.addr_validate(&msg.base)?;
+    BASE.save(deps.storage, &base)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "instantiate")
+        .add_attribute("base", base))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn execute(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response, ContractError> {
+    let base = BASE.load(deps.storage)?;
+
+    match msg {
+        ExecuteMsg::TransferNft {
+            recipient,
+            token_id,
+        } => Ok(Response::new()
+            .add_message(relay(
+                &base,
+                &info.sender,
+                MirrorMsg::TransferNft {
+                    recipient: recipient.clone(),
+                    token_id: token_id.clone(),
+                },
+            )?)
+            .add_event(transfer_nft_event(&info.sender, &recipient, &token_id))
+            .add_attribute("action", "transfer_nft")
+            .add_attribute("sender", info.sender)
+            .add_attribute("recipient", recipient)
+            .add_attribute("token_id", token_id)),
+        ExecuteMsg::SendNft {
+            contract,
+            token_id,
+            msg,
+        } => {
+            // the base moves the token, the hook has to come from the
+            // collection address the receiver knows, which is this one
+            let transfer = relay(
+                &base,
+                &info.sender,
+                MirrorMsg::TransferNft {
+                    recipient: contract.clone(),
+                    token_id: token_id.clone(),
+                },
+            )?;
+            let receive = Cw721ReceiveMsg {
+                sender: info.sender.to_string(),
+                token_id: token_id.clone(),
+                msg,
+            }
+            .into_cosmos_msg(contract.clone())?;
+
+            Ok(Response::new()
+                .add_message(transfer)
+                .add_message(receive)
+                .add_event(transfer_nft_event(&info.sender, &contract, &token_id))
+                .add_attribute("action", "send_nft")
+                .add_attribute("sender", info.sender)
+                .add_attribute("recipient", contract)
+                .add_attribute("token_id", token_id))
+        }
+        ExecuteMsg::Approve {
+            spender,
+            token_id,
+            expires,
+        } => {
+            reject_expiration(expires)?;
+            Ok(Response::new()
+                .add_message(relay(
+                    &base,
+                    &info.sender,
+                    MirrorMsg::Approve {
+                        spender: spender.clone(),
+                        token_id: token_id.clone(),
+                    },
+                )?)
+                .add_attribute("action", "approve")
+                .add_attribute("sender", info.sender)
+                .add_attribute("spender", spender)
+                .add_attribute("token_id", token_id))
+        }
+        ExecuteMsg::Revoke { spender, token_id } => Ok(Response::new()
+            .add_message(relay(
+                &base,
+                &info.sender,
+                MirrorMsg::Revoke {
+                    spender: spender.clone(),
+                    token_id: token_id.clone(),
+                },
+            )?)
+            .add_attribute("action", "revoke")
+            .add_attribute("sender", info.sender)
+            .add_attribute("spender", spender)
+            .add_attribute("token_id", token_id)),
+        ExecuteMsg::ApproveAll { operator, expires } => {
+            reject_expiration(expires)?;
+            Ok(Response::new()
+                .add_message(relay(
+                    &base,
+                    &info.sender,
+                    MirrorMsg::ApproveAll {
+                        operator: operator.clone(),
+                    },
+                )?)
+                .add_attribute("action", "approve_all")
+                .add_attribute("sender", info.sender)
+                .add_attribute("operator", operator))
+        }
+        ExecuteMsg::RevokeAll { operator } => Ok(Response::new()
+            .add_message(relay(
+                &base,
+                &info.sender,
+                MirrorMsg::RevokeAll {
+                    operator: operator.clone(),
+                },
+            )?)
+            .add_attribute("action", "revoke_all")
+            .add_attribute("sender", info.sender)
+            .add_attribute("operator", operator)),
+    }
+}
+
+fn relay(base: &Addr, sender: &Addr, msg: MirrorMsg) -> StdResult<CosmosMsg> {
+    Ok(WasmMsg::Execute {
+        contract_addr: base.to_string(),
+        msg: to_json_binary(&BaseExecuteMsg::Mirror {
+            sender: sender.to_string(),
+            msg,
+        })?,
+        funds: vec![],
+    }
+    .into())
+}
+
+/// Marketplaces and indexers follow the collection address, so every relayed
+/// move is reported from here as well as from the base.
+fn transfer_nft_event(sender: &Addr, recipient: &str, token_id: &str) -> Event {
+    Event::new("transfer_nft")
+        .add_attribute("sender", sender)
+        .add_attribute("recipient", recipient)
+        .add_attribute("token_id", token_id)
+}
+
+fn reject_expiration(expires: Option<Expiration>) -> Result<(), ContractError> {
+    match expires {
+        None | Some(Expiration::Never {}) => Ok(()),
+        Some(_) => Err(ContractError::ExpirationUnsupported {}),
+    }
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+    let stored = get_contract_version(deps.storage)?;
+    if stored.contract != CONTRACT_NAME
+        || stored.version.parse::<Version>()? > CONTRACT_VERSION.parse::<Version>()?
+    {
+        return Err(ContractError::CannotMigrate {
+            contract: stored.contract,
+            version: stored.version,
+        });
+    }
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "migrate")
+        .add_attribute("from_version", stored.version)
+        .add_attribute("to_version", CONTRACT_VERSION))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    let base = BASE.load(deps.storage)?;
+
+    match msg {
+        QueryMsg::OwnerOf { token_id, .. } => {
+            to_json_binary(&query_owner_of(deps, &base, token_id)?)
+        }
+        QueryMsg::Approval {
+            token_id, spender, ..
+        } => query_approvals(deps, &base, token_id)?
+            .into_iter()
+            .find(|approval| approval.spender == spender)
+            .map(|approval| to_json_binary(&ApprovalResponse { approval }))
+            .unwrap_or_else(|| Err(StdError::not_found("Approval not found"))),
+        QueryMsg::Approvals { token_id, .. } => to_json_binary(&ApprovalsResponse {
+            approvals: query_approvals(deps, &base, token_id)?,
+        }),
+        QueryMsg::NumTokens {} => {
+            // burned ids are never reused, only live NFTs count
+            let stats: SupplyStatsResponse = deps
+                .querier
+                .query_wasm_smart(&base, &BaseQueryMsg::SupplyStats {})?;
+            to_json_binary(&NumTokensResponse {
+                count: stats.live_nfts.u128() as u64,
+            })
+        }
+        QueryMsg::ContractInfo {} => {
+            let token_info: TokenInfoResponse = deps
+                .querier
+                .query_wasm_smart(&base, &BaseQueryMsg::TokenInfo {})?;
+            to_json_binary(&ContractInfoResponse {
+                name: token_info.name,
+                symbol: token_info.symbol,
+            })
+        }
+        QueryMsg::NftInfo { token_id } => to_json_binary(&query_nft_info(deps, &base, token_id)?),
+        QueryMsg::AllNftInfo { token_id, .. } => to_json_binary(&AllNftInfoResponse {
+            access: query_owner_of(deps, &base, token_id.clone())?,
+            info: query_nft_info(deps, &base, token_id)?,
+        }),
+        QueryMsg::Tokens {
+            owner,
+            start_after,
+            limit,
+        } => to_json_binary(&query_tokens(deps, &base, owner, start_after, limit)?),
+        QueryMsg::AllTokens { start_after, limit } => {
+            let tokens: TokensResponse = deps
+                .querier
+                .query_wasm_smart(&base, &BaseQueryMsg::AllTokens { start_after, limit })?;
+            to_json_binary(&tokens)
+        }
+        QueryMsg::Base {} => to_json_binary(&base),
+    }
+}
+
+fn query_owner_of(deps: Deps, base: &Addr, token_id: String) -> StdResult<OwnerOfResponse> {
+    let owner_of: OwnerOfResponse = deps.querier.query_wasm_smart(
+        base,
+        &BaseQueryMsg::OwnerOf {
+            token_id: token_id.clone(),
+        },
+    )?;
+    Ok(OwnerOfResponse {
+        owner: owner_of.owner,
+        approvals: query_approvals(deps, base, token_id)?,
+    })
+}
+
+fn query_approvals(deps: Deps, base: &Addr, token_id: String) -> StdResult<Vec<Approval>> {
+    let approved: Option<Addr> = deps
+        .querier
+        .query_wasm_smart(base, &BaseQueryMsg::GetApproved { token_id })?;
+    Ok(approved
+        .into_iter()
+        .map(|spender| Approval {
+            spender: spender.into_string(),
+            expires: Expiration::Never {},
+        })
+        .collect())
+}
+
+fn query_nft_info(
+    deps: Deps,
+    base: &Addr,
+    token_id: String,
+) -> StdResult<NftInfoResponse<NftExtension>> {
+    deps.querier
+        .query_wasm_smart(base, &BaseQueryMsg::NftInfo { token_id })
+}
+
+/// The base returns an owner's tokens in holding order, cw721 clients expect
+/// them sorted and paginated.
+fn query_tokens(
+    deps: Deps,
+    base: &Addr,
+    owner: String,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<TokensResponse> {
+    let owned: TokensResponse = deps
+        .querier
+        .query_wasm_smart(base, &BaseQueryMsg::Tokens { owner })?;
+
+    let start_after = start_after
+        .map(|start_after| {
+            start_after
+                .parse::<u128>()
+                .map_err(|_| StdError::generic_err("Invalid token ID"))
+        })
+        .transpose()?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+    let mut ids = owned
+        .tokens
+        .iter()
+        .map(|id| {
+            id.parse::<u128>()
+                .map_err(|_| StdError::generic_err("Invalid token ID"))
+        })
+        .collect::<StdResult<Vec<u128>>>()?;
+    ids.sort_unstable();
+
+    Ok(TokensResponse {
+        tokens: ids
+            .into_iter()
+            .skip_while(|id| start_after.is_some_and(|start_after| *id <= start_after))
+            .take(limit)
+            .map(|id| id.to_string())
+            .collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
+    use cosmwasm_std::{from_json, ContractResult, SystemResult, Uint128, WasmQuery};
+
+    #[test]
+    fn relays_transfer_with_original_sender() {
+        let mut deps = mock_dependencies();
+
+        let msg = InstantiateMsg {
+            base: String::from("cw404"),
+        };
+        let info = mock_info("creator", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let msg = ExecuteMsg::SendNft {
+            contract: String::from("market"),
+            token_id: "1".to_string(),
+            msg: Binary::default(),
+        };
+        let info = mock_info("alice", &[]);
+        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        assert_eq!(res.messages.len(), 2);
+        assert_eq!(
+            res.events,
+            vec![Event::new("transfer_nft")
+                .add_attribute("sender", "alice")
+                .add_attribute("recipient", "market")
+                .add_attribute("token_id", "1")]
+        );
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: String::from("cw404"),
+                msg: to_json_binary(&BaseExecuteMsg::Mirror {
+                    sender: String::from("alice"),
+                    msg: MirrorMsg::TransferNft {
+                        recipient: String::from("market"),
+                        token_id: "1".to_string(),
+                    },
+                })
+                .unwrap(),
+                funds: vec![],
+            })
+        );
+        assert_eq!(
+            res.messages[1].msg,
+            Cw721ReceiveMsg {
+                sender: String::from("alice"),
+                token_id: "1".to_string(),
+                msg: Binary::default(),
+            }
+            .into_cosmos_msg("market")
+            .unwrap()
+        );
+
+        let msg = ExecuteMsg::ApproveAll {
+            operator: String::from("bob"),
+            expires: Some(Expiration::AtHeight(1)),
+        };
+        let info = mock_info("alice", &[]);
+        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert!(matches!(err, ContractError::ExpirationUnsupported {}));
+    }
+
+    #[test]
+    fn answers_cw721_queries_from_base() {
+        let mut deps = mock_dependencies();
+
+        let msg = InstantiateMsg {
+            base: String::from("cw404"),
+        };
+        let info = mock_info("creator", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        deps.querier.update_wasm(|query| match query {
+            WasmQuery::Smart { msg, .. } => {
+                let res = match from_json(msg).unwrap() {
+                    BaseQueryMsg::OwnerOf { .. } => to_json_binary(&OwnerOfResponse {
+                        owner: String::from("alice"),
+                        approvals: vec![],
+                    }),
+                    BaseQueryMsg::GetApproved { .. } => {
+                        to_json_binary(&Some(Addr::unchecked("bob")))
+                    }
+                    BaseQueryMsg::Tokens { .. } => to_json_binary(&TokensResponse {
+                        tokens: vec!["7".to_string(), "2".to_string(), "5".to_string()],
+                    }),
+                    BaseQueryMsg::SupplyStats {} => to_json_binary(&SupplyStatsResponse {
+                        total_supply: Uint128::new(8_000_000),
+                        contract_balance: Uint128::new(5_000_000),
+                        circulating: Uint128::new(3_000_000),
+                        live_nfts: Uint128::new(3),
+                    }),
+                    _ => panic!("unexpected query"),
+                };
+                SystemResult::Ok(ContractResult::Ok(res.unwrap()))
+            }
+            _ => panic!("unexpected query"),
+        });
+
+        let msg = QueryMsg::OwnerOf {
+            token_id: "2".to_string(),
+            include_expired: None,
+        };
+        let owner_of: OwnerOfResponse =
+            from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(owner_of.owner, "alice");
+        assert_eq!(
+            owner_of.approvals,
+            vec![Approval {
+                spender: String::from("bob"),
+                expires: Expiration::Never {},
+            }]
+        );
+
+        let msg = QueryMsg::Tokens {
+            owner: String::from("alice"),
+            start_after: Some("2".to_string()),
+            limit: None,
+        };
+        let tokens: TokensResponse =
+            from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(tokens.tokens, vec!["5".to_string(), "7".to_string()]);
+
+        let num_tokens: NumTokensResponse =
+            from_json(query(deps.as_ref(), mock_env(), QueryMsg::NumTokens {}).unwrap()).unwrap();
+        assert_eq!(num_tokens.count, 3);
+    }
+
+    #[test]
+    fn migrate_checks_stored_version() {
+        let mut deps = mock_dependencies();
+
+        let msg = InstantiateMsg {
+            base: String::from("cw404"),
+        };
+        let info = mock_info("creator", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+        migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+
+        set_contract_version(deps.as_mut().storage, CONTRACT_NAME, "9.0.0").unwrap();
+        let err = migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap_err();
+        assert!(matches!(err, ContractError::CannotMigrate { .. }));
+    }
+}
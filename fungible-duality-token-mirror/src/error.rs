@@ -0,0 +1,17 @@
+use cosmwasm_std::StdError;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum ContractError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    #[error("{0}")]
+    SemVer(#[from] semver::Error),
+
+    #[error("Approvals do not expire on the duality token")]
+    ExpirationUnsupported {},
+
+    #[error("Cannot migrate from {contract} {version}")]
+    CannotMigrate { contract: String, version: String },
+}
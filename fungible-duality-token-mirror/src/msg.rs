@@ -0,0 +1,96 @@
+use cosmwasm_schema::{cw_serde, QueryResponses};
+use cosmwasm_std::Binary;
+use cw404::msg::NftExtension;
+use cw721::{
+    AllNftInfoResponse, ApprovalResponse, ApprovalsResponse, ContractInfoResponse, Expiration,
+    NftInfoResponse, NumTokensResponse, OwnerOfResponse, TokensResponse,
+};
+
+#[cw_serde]
+pub struct InstantiateMsg {
+    /// Address of the fungible-duality-token contract
+    pub base: String,
+}
+
+/// The cw721 execute interface, minus `Burn`. Every call is relayed to the
+/// base contract with the caller as sender.
+#[cw_serde]
+pub enum ExecuteMsg {
+    TransferNft {
+        recipient: String,
+        token_id: String,
+    },
+    SendNft {
+        contract: String,
+        token_id: String,
+        msg: Binary,
+    },
+    /// Only `expires: None` or `Never` is accepted
+    Approve {
+        spender: String,
+        token_id: String,
+        expires: Option<Expiration>,
+    },
+    Revoke {
+        spender: String,
+        token_id: String,
+    },
+    /// Only `expires: None` or `Never` is accepted
+    ApproveAll {
+        operator: String,
+        expires: Option<Expiration>,
+    },
+    RevokeAll {
+        operator: String,
+    },
+}
+
+/// The cw721 query interface, answered from the base contract's state.
+/// `include_expired` is accepted for compatibility, approvals never expire.
+#[cw_serde]
+#[derive(QueryResponses)]
+pub enum QueryMsg {
+    #[returns(OwnerOfResponse)]
+    OwnerOf {
+        token_id: String,
+        include_expired: Option<bool>,
+    },
+    #[returns(ApprovalResponse)]
+    Approval {
+        token_id: String,
+        spender: String,
+        include_expired: Option<bool>,
+    },
+    #[returns(ApprovalsResponse)]
+    Approvals {
+        token_id: String,
+        include_expired: Option<bool>,
+    },
+    #[returns(NumTokensResponse)]
+    NumTokens {},
+    #[returns(ContractInfoResponse)]
+    ContractInfo {},
+    #[returns(NftInfoResponse<NftExtension>)]
+    NftInfo { token_id: String },
+    #[returns(AllNftInfoResponse<NftExtension>)]
+    AllNftInfo {
+        token_id: String,
+        include_expired: Option<bool>,
+    },
+    #[returns(TokensResponse)]
+    Tokens {
+        owner: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    #[returns(TokensResponse)]
+    AllTokens {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    #[returns(cosmwasm_std::Addr)]
+    Base {},
+}
+
+#[cw_serde]
+pub struct MigrateMsg {}
@@ -0,0 +1,5 @@
+use cosmwasm_std::Addr;
+use cw_storage_plus::Item;
+
+/// The fungible-duality-token contract this mirror fronts
+pub const BASE: Item<Addr> = Item::new("base");
@@ -1,25 +1,51 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    to_json_binary, Addr, Binary, CosmosMsg, Deps, DepsMut, Empty, Env, MessageInfo, Response,
-    StdError, StdResult, Storage, Uint128,
+    coins, to_json_binary, Addr, BankMsg, Binary, BlockInfo, CosmosMsg, Deps, DepsMut, Env, Event,
+    MessageInfo, Order, QuerierWrapper, Response, StdError, StdResult, Storage, Timestamp, Uint128,
 };
-use cw2::set_contract_version;
-use cw20::{BalanceResponse, Cw20ReceiveMsg, MinterResponse, TokenInfoResponse};
-use cw404_utils::{check_funds, process_fee, CREATOR, INSTANTIATE_FEE, MINT_FEE};
-use cw721::{Cw721ReceiveMsg, NftInfoResponse, NumTokensResponse, OwnerOfResponse, TokensResponse};
+use cw2::{get_contract_version, set_contract_version};
+use cw20::{
+    AllAccountsResponse, BalanceResponse, Cw20Contract, Cw20ExecuteMsg, Cw20ReceiveMsg, Denom,
+    DownloadLogoResponse, EmbeddedLogo, Logo, LogoInfo, MinterResponse, TokenInfoResponse,
+};
+use cw721::{
+    ContractInfoResponse, Cw721ReceiveMsg, NftInfoResponse, NumTokensResponse, OwnerOfResponse,
+    TokensResponse,
+};
+use cw_ownable::Action;
+use cw_storage_plus::Bound;
+use cw_utils::{must_pay, nonpayable};
+use semver::Version;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
 
 use crate::error::ContractError;
-use crate::msg::{ConfigResponse, ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg};
+use crate::msg::{
+    AccountingMismatch, BatchReceiveNftMsg, ClaimConfigMsg, ClaimStatusResponse, ConfigResponse,
+    DualityInfoResponse, ExecuteMsg, InstantiateMsg, MigrateMsg, MirrorMsg, NftExtension, QueryMsg,
+    ReserveResponse, SupplyStatsResponse, Trait, VerifyAccountingResponse, VerifyTokensResponse,
+    WhitelistedResponse,
+};
 use crate::state::{
-    Config, Metadata, ALLOWANCE, BALANCE_OF, CONFIG, GET_APPROVED, IS_APPROVED_FOR_ALL,
-    LAST_MINT_SECONDS, METADATA, MINTED, OWNED, OWNED_INDEX, OWNER_OF, WHITELIST,
+    ClaimConfig, Metadata, NftMetadata, Role, Roles, TraitDef, ALLOWANCE, BALANCE_OF, CLAIMED,
+    CLAIM_CONFIG, CONFIG, GET_APPROVED, HOLDER_COUNT, IS_APPROVED_FOR_ALL, LAST_MINT_SECONDS,
+    LIVE_NFTS, LOCKED, LOGO, MARKETING_INFO, METADATA, MINTED, MIRROR, NFT_METADATA, OWNED,
+    OWNED_INDEX, OWNER_OF, ROLES, SKIP_NFT, TAX_CONFIG, TOKEN_ENTROPY, TOTAL_CLAIMED, UNDERLYING,
+    WHITELIST,
 };
 
 // version info for migration info
 const CONTRACT_NAME: &str = "crates.io:cw404";
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
+const DEFAULT_LIMIT: u32 = 10;
+const MAX_LIMIT: u32 = 100;
+// upper bound on the ids or recipients handled by one batch call
+const MAX_BATCH: usize = 50;
+// 10%
+const MAX_TAX_BPS: u16 = 1_000;
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
     deps: DepsMut,
@@ -29,33 +55,52 @@ pub fn instantiate(
 ) -> Result<Response, ContractError> {
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
 
-    let mut msgs: Vec<CosmosMsg> = vec![];
+    let msgs: Vec<CosmosMsg> = vec![];
 
     // if info.sender.to_string() != CREATOR {
     //     check_funds(&info, INSTANTIATE_FEE)?;
     //     process_fee(INSTANTIATE_FEE, &mut msgs)?;
     // }
 
-    let config = Config {
-        admin: info.sender.clone(),
-    };
-    CONFIG.save(deps.storage, &config)?;
+    if msg.units_per_nft.is_zero() {
+        return Err(ContractError::InvalidUnitsPerNft {});
+    }
+
+    if let Some(underlying) = msg.underlying {
+        if msg.total_native_supply != 0 {
+            return Err(ContractError::InvalidInput {});
+        }
+        let underlying = match underlying {
+            Denom::Native(denom) => Denom::Native(denom),
+            Denom::Cw20(addr) => Denom::Cw20(deps.api.addr_validate(addr.as_str())?),
+        };
+        UNDERLYING.save(deps.storage, &underlying)?;
+    }
+
+    cw_ownable::initialize_owner(deps.storage, deps.api, Some(info.sender.as_str()))?;
+    ROLES.save(deps.storage, &Roles::default())?;
+
+    let total_supply = Uint128::new(msg.total_native_supply)
+        .checked_mul(Uint128::new(10).checked_pow(msg.decimals as u32)?)?;
 
     let metadata = Metadata {
         name: msg.name,
         symbol: msg.symbol,
         decimals: msg.decimals,
-        total_supply: msg.total_native_supply * ((10u32).pow(msg.decimals as u32) as u128),
+        total_supply: total_supply.u128(),
         token_uri: msg.token_uri,
+        units_per_nft: msg.units_per_nft,
     };
     METADATA.save(deps.storage, &metadata)?;
 
     MINTED.save(deps.storage, &0)?;
+    LIVE_NFTS.save(deps.storage, &0)?;
+    HOLDER_COUNT.save(deps.storage, &0)?;
 
-    BALANCE_OF.save(
+    save_balance(
         deps.storage,
-        env.contract.address.clone(),
-        &Uint128::new(metadata.total_supply),
+        &env.contract.address,
+        Uint128::new(metadata.total_supply),
     )?;
 
     WHITELIST.save(deps.storage, env.contract.address.clone(), &true)?;
@@ -74,6 +119,10 @@ pub fn execute(
         ExecuteMsg::SetWhitelist { target, state } => {
             execute_set_whitelist(deps, env, info, target, state)
         }
+        ExecuteMsg::SetSkipNft { skip } => execute_set_skip_nft(deps, env, info, skip),
+        ExecuteMsg::Resync { start_after, limit } => execute_resync(deps, env, start_after, limit),
+        ExecuteMsg::Lock { token_ids } => execute_set_locked(deps, env, info, token_ids, true),
+        ExecuteMsg::Unlock { token_ids } => execute_set_locked(deps, env, info, token_ids, false),
         ExecuteMsg::Approve {
             spender,
             amount_or_id,
@@ -107,87 +156,718 @@ pub fn execute(
             amount,
         } => execute_transfer_from(deps, env, info, owner, recipient, amount),
         // wrapper code
-        ExecuteMsg::Mint {} => {
-            let mut msgs: Vec<CosmosMsg> = vec![];
-
-            // check_funds(&info, MINT_FEE)?;
-            // process_fee(MINT_FEE, &mut msgs)?;
-
-            if BALANCE_OF.load(deps.storage, env.contract.address.clone())? == Uint128::zero() {
-                return Err(ContractError::OutOfSupply {});
-            }
-
-            let current_seconds = env.block.time.nanos() / 1_000_000_000;
-            let last_mint_seconds = LAST_MINT_SECONDS
-                .load(deps.storage, info.sender.clone())
-                .unwrap_or(0);
-
-            if current_seconds < last_mint_seconds + 86_400 {
-                return Err(ContractError::AlreadyMintedToday {});
-            }
-
-            LAST_MINT_SECONDS.save(deps.storage, info.sender.clone(), &current_seconds)?;
-
-            let unit = get_unit(deps.storage)?.u128();
-            execute_transfer(
-                deps,
-                env.clone(),
-                info.clone(),
-                Some(env.contract.address.to_string()),
-                info.sender.to_string(),
-                unit,
-                msgs,
-            )
+        ExecuteMsg::Mint { proof } => execute_mint(deps, env, info, proof),
+        ExecuteMsg::UpdateNftMetadata {
+            base_uri,
+            placeholder_uri,
+            traits,
+        } => execute_update_nft_metadata(deps, env, info, base_uri, placeholder_uri, traits),
+        ExecuteMsg::Reveal {} => execute_reveal(deps, env, info),
+        ExecuteMsg::SetMirror { mirror } => execute_set_mirror(deps, env, info, mirror),
+        ExecuteMsg::Mirror { sender, msg } => execute_mirror(deps, env, info, sender, msg),
+        ExecuteMsg::UpdateOwnership(action) => execute_update_ownership(deps, env, info, action),
+        ExecuteMsg::SetRole { role, address } => execute_set_role(deps, env, info, role, address),
+        ExecuteMsg::UpdateClaimConfig { config } => {
+            execute_update_claim_config(deps, env, info, config)
         }
         ExecuteMsg::AirdropNft {
             recipient,
             nft_amount,
         } => {
-            let config = CONFIG.load(deps.storage)?;
-            if info.sender != config.admin {
-                return Err(ContractError::Unauthorized {});
-            }
+            assert_role(deps.storage, &info.sender, Role::AirdropOperator)?;
 
-            let unit = get_unit(deps.storage)?.u128();
+            let amount = Uint128::new(nft_amount).checked_mul(get_unit(deps.storage)?)?;
             execute_transfer(
                 deps,
                 env.clone(),
                 info,
                 Some(env.contract.address.to_string()),
                 recipient,
-                nft_amount * unit,
+                amount.u128(),
                 vec![],
             )
         }
+        ExecuteMsg::BatchTransferNft {
+            recipient,
+            token_ids,
+        } => execute_batch_transfer_nft(deps, env, info, recipient, token_ids, None),
+        ExecuteMsg::BatchSendNft {
+            contract,
+            token_ids,
+            msg,
+        } => execute_batch_transfer_nft(deps, env, info, contract, token_ids, Some(msg)),
+        ExecuteMsg::BatchAirdrop { recipients } => {
+            execute_batch_airdrop(deps, env, info, recipients)
+        }
+        ExecuteMsg::Receive(msg) => {
+            if UNDERLYING.may_load(deps.storage)? != Some(Denom::Cw20(info.sender.clone())) {
+                return Err(ContractError::Unauthorized {});
+            }
+            let sender = deps.api.addr_validate(&msg.sender)?;
+            execute_wrap(deps, env, sender, msg.amount)
+        }
+        ExecuteMsg::Wrap {} => match UNDERLYING.may_load(deps.storage)? {
+            Some(Denom::Native(denom)) => {
+                let amount = must_pay(&info, &denom)?;
+                execute_wrap(deps, env, info.sender, amount)
+            }
+            _ => Err(ContractError::NotWrapper {}),
+        },
+        ExecuteMsg::Unwrap { amount } => execute_unwrap(deps, env, info, amount),
+        ExecuteMsg::UpdateMarketing {
+            project,
+            description,
+            marketing,
+        } => execute_update_marketing(deps, env, info, project, description, marketing),
+        ExecuteMsg::UploadLogo(logo) => execute_upload_logo(deps, env, info, logo),
+        ExecuteMsg::UpdateTaxConfig {
+            bps,
+            treasury,
+            add_exempt,
+            remove_exempt,
+        } => execute_update_tax_config(deps, env, info, bps, treasury, add_exempt, remove_exempt),
+        ExecuteMsg::UpdateCollectionInfo {
+            name,
+            symbol,
+            token_uri,
+        } => execute_update_collection_info(deps, env, info, name, symbol, token_uri),
     }
 }
 
-fn execute_set_whitelist(
+fn execute_update_ownership(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    action: Action,
+) -> Result<Response, ContractError> {
+    let ownership = cw_ownable::update_ownership(deps, &env.block, &info.sender, action)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "update_ownership")
+        .add_attributes(ownership.into_attributes()))
+}
+
+fn execute_set_role(
     deps: DepsMut,
     _env: Env,
     info: MessageInfo,
+    role: Role,
+    address: Option<String>,
+) -> Result<Response, ContractError> {
+    cw_ownable::assert_owner(deps.storage, &info.sender)?;
+
+    let holder = address
+        .as_ref()
+        .map(|address| deps.api.addr_validate(address))
+        .transpose()?;
+    let mut roles = ROLES.may_load(deps.storage)?.unwrap_or_default();
+    match role {
+        Role::WhitelistManager => roles.whitelist_manager = holder,
+        Role::AirdropOperator => roles.airdrop_operator = holder,
+    }
+    ROLES.save(deps.storage, &roles)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "set_role")
+        .add_attribute("role", role.to_string())
+        .add_attribute("address", address.unwrap_or_else(|| "none".to_string())))
+}
+
+fn execute_set_whitelist(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
     target: String,
     state: bool,
 ) -> Result<Response, ContractError> {
     let addr = deps.api.addr_validate(&target)?;
 
-    let config = CONFIG.load(deps.storage)?;
-    if info.sender != config.admin {
-        return Err(ContractError::Unauthorized {});
-    }
+    assert_role(deps.storage, &info.sender, Role::WhitelistManager)?;
 
-    WHITELIST.save(deps.storage, addr, &state)?;
+    WHITELIST.save(deps.storage, addr.clone(), &state)?;
+    let events = _sync_nfts(deps.storage, &deps.querier, &env.block, addr)?;
 
     Ok(Response::new()
+        .add_events(events)
         .add_attribute("action", "set_whitelist")
         .add_attribute("address", target)
         .add_attribute("state", state.to_string()))
 }
 
-fn execute_transfer(
+fn execute_set_skip_nft(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    skip: bool,
+) -> Result<Response, ContractError> {
+    SKIP_NFT.save(deps.storage, info.sender.clone(), &skip)?;
+    let events = if skip {
+        _burn_unlocked(deps.storage, info.sender.clone())?
+    } else {
+        _sync_nfts(deps.storage, &deps.querier, &env.block, info.sender.clone())?
+    };
+
+    Ok(Response::new()
+        .add_events(events)
+        .add_attribute("action", "set_skip_nft")
+        .add_attribute("address", info.sender)
+        .add_attribute("skip", skip.to_string()))
+}
+
+fn execute_resync(
+    deps: DepsMut,
+    env: Env,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> Result<Response, ContractError> {
+    let start = start_after
+        .map(|addr| deps.api.addr_validate(&addr))
+        .transpose()?
+        .map(Bound::exclusive);
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+    let holders = BALANCE_OF
+        .keys(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .collect::<StdResult<Vec<Addr>>>()?;
+
+    let mut res = Response::new()
+        .add_attribute("action", "resync")
+        .add_attribute("checked", holders.len().to_string());
+    if let Some(last) = holders.last() {
+        res = res.add_attribute("last", last);
+    }
+    for holder in holders {
+        res = res.add_events(_sync_nfts(deps.storage, &deps.querier, &env.block, holder)?);
+    }
+
+    Ok(res)
+}
+
+fn execute_set_locked(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    token_ids: Vec<String>,
+    locked: bool,
+) -> Result<Response, ContractError> {
+    for token_id in &token_ids {
+        let id: u128 = token_id.parse()?;
+        if info.sender != OWNER_OF.load(deps.storage, id)? {
+            return Err(ContractError::Unauthorized {});
+        }
+
+        if locked {
+            LOCKED.save(deps.storage, id, &true)?;
+        } else {
+            LOCKED.remove(deps.storage, id);
+        }
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", if locked { "lock" } else { "unlock" })
+        .add_attribute("owner", info.sender)
+        .add_attribute("token_ids", token_ids.join(",")))
+}
+
+fn execute_mint(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    proof: Option<Vec<String>>,
+) -> Result<Response, ContractError> {
+    let claim = load_claim_config(deps.storage)?;
+    let now = env.block.time;
+
+    if claim.start_time.is_some_and(|start| now < start) {
+        return Err(ContractError::ClaimNotStarted {});
+    }
+    if claim.end_time.is_some_and(|end| now >= end) {
+        return Err(ContractError::ClaimEnded {});
+    }
+    if let Some(merkle_root) = &claim.merkle_root {
+        verify_claim_proof(merkle_root, &info.sender, proof.unwrap_or_default())?;
+    }
+
+    let mut msgs: Vec<CosmosMsg> = vec![];
+    match (&claim.fee, &claim.treasury) {
+        (Some(fee), Some(treasury)) => {
+            if must_pay(&info, &fee.denom)? != fee.amount {
+                return Err(ContractError::InvalidFunds {});
+            }
+            msgs.push(
+                BankMsg::Send {
+                    to_address: treasury.to_string(),
+                    amount: vec![fee.clone()],
+                }
+                .into(),
+            );
+        }
+        _ => nonpayable(&info)?,
+    }
+
+    let current_seconds = now.seconds();
+    if let Some(last_mint_seconds) =
+        LAST_MINT_SECONDS.may_load(deps.storage, info.sender.clone())?
+    {
+        let next_claim = last_mint_seconds.saturating_add(claim.cooldown);
+        if current_seconds < next_claim {
+            return Err(ContractError::ClaimCooldown { next_claim });
+        }
+    }
+
+    let claimed = CLAIMED
+        .may_load(deps.storage, info.sender.clone())?
+        .unwrap_or_default()
+        .checked_add(claim.amount)?;
+    if claim.per_address_cap.is_some_and(|cap| claimed > cap) {
+        return Err(ContractError::ClaimCapReached {});
+    }
+    let total_claimed = TOTAL_CLAIMED
+        .may_load(deps.storage)?
+        .unwrap_or_default()
+        .checked_add(claim.amount)?;
+    if claim.global_cap.is_some_and(|cap| total_claimed > cap) {
+        return Err(ContractError::ClaimCapReached {});
+    }
+
+    let available = BALANCE_OF
+        .may_load(deps.storage, env.contract.address.clone())?
+        .unwrap_or_default();
+    if available < claim.amount {
+        return Err(ContractError::OutOfSupply {});
+    }
+
+    LAST_MINT_SECONDS.save(deps.storage, info.sender.clone(), &current_seconds)?;
+    CLAIMED.save(deps.storage, info.sender.clone(), &claimed)?;
+    TOTAL_CLAIMED.save(deps.storage, &total_claimed)?;
+
+    execute_transfer(
+        deps,
+        env.clone(),
+        info.clone(),
+        Some(env.contract.address.to_string()),
+        info.sender.to_string(),
+        claim.amount.u128(),
+        msgs,
+    )
+}
+
+fn execute_update_claim_config(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    config: ClaimConfigMsg,
+) -> Result<Response, ContractError> {
+    cw_ownable::assert_owner(deps.storage, &info.sender)?;
+
+    if config.amount.is_zero() {
+        return Err(ContractError::InvalidInput {});
+    }
+    if let (Some(start), Some(end)) = (config.start_time, config.end_time) {
+        if start >= end {
+            return Err(ContractError::InvalidInput {});
+        }
+    }
+    if let Some(merkle_root) = &config.merkle_root {
+        let mut root_buf: [u8; 32] = [0; 32];
+        hex::decode_to_slice(merkle_root, &mut root_buf)?;
+    }
+    let treasury = config
+        .treasury
+        .map(|treasury| deps.api.addr_validate(&treasury))
+        .transpose()?;
+    if let Some(fee) = &config.fee {
+        if fee.amount.is_zero() || treasury.is_none() {
+            return Err(ContractError::InvalidInput {});
+        }
+    }
+
+    let claim = ClaimConfig {
+        amount: config.amount,
+        cooldown: config.cooldown,
+        per_address_cap: config.per_address_cap,
+        global_cap: config.global_cap,
+        start_time: config.start_time,
+        end_time: config.end_time,
+        fee: config.fee,
+        treasury,
+        merkle_root: config.merkle_root,
+    };
+    CLAIM_CONFIG.save(deps.storage, &claim)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "update_claim_config")
+        .add_attribute("amount", claim.amount)
+        .add_attribute("cooldown", claim.cooldown.to_string()))
+}
+
+fn execute_update_nft_metadata(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    base_uri: Option<String>,
+    placeholder_uri: Option<String>,
+    traits: Option<Vec<TraitDef>>,
+) -> Result<Response, ContractError> {
+    cw_ownable::assert_owner(deps.storage, &info.sender)?;
+
+    let mut nft_metadata = load_nft_metadata(deps.storage)?;
+    let mut res = Response::new().add_attribute("action", "update_nft_metadata");
+
+    if let Some(base_uri) = base_uri {
+        res = res.add_attribute("base_uri", base_uri.clone());
+        nft_metadata.base_uri = base_uri;
+    }
+    match placeholder_uri {
+        Some(empty) if empty.trim().is_empty() => {
+            res = res.add_attribute("placeholder_uri", "none");
+            nft_metadata.placeholder_uri = None;
+        }
+        Some(placeholder_uri) => {
+            res = res.add_attribute("placeholder_uri", placeholder_uri.clone());
+            nft_metadata.placeholder_uri = Some(placeholder_uri);
+        }
+        None => (),
+    }
+    if let Some(traits) = traits {
+        // revealed traits are final
+        if nft_metadata.reveal_seed.is_some() {
+            return Err(ContractError::AlreadyRevealed {});
+        }
+        if traits.iter().any(|trait_def| trait_def.values.is_empty()) {
+            return Err(ContractError::InvalidInput {});
+        }
+        res = res.add_attribute("traits", traits.len().to_string());
+        nft_metadata.traits = traits;
+    }
+
+    NFT_METADATA.save(deps.storage, &nft_metadata)?;
+
+    Ok(res)
+}
+
+fn execute_reveal(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, ContractError> {
+    cw_ownable::assert_owner(deps.storage, &info.sender)?;
+
+    let mut nft_metadata = load_nft_metadata(deps.storage)?;
+    if nft_metadata.reveal_seed.is_some() {
+        return Err(ContractError::AlreadyRevealed {});
+    }
+
+    let seed = token_entropy(&env.block, MINTED.load(deps.storage)?);
+    nft_metadata.reveal_seed = Some(seed.clone());
+    NFT_METADATA.save(deps.storage, &nft_metadata)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "reveal")
+        .add_attribute("seed", seed.to_base64()))
+}
+
+fn execute_set_mirror(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    mirror: String,
+) -> Result<Response, ContractError> {
+    cw_ownable::assert_owner(deps.storage, &info.sender)?;
+
+    MIRROR.save(deps.storage, &deps.api.addr_validate(&mirror)?)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "set_mirror")
+        .add_attribute("mirror", mirror))
+}
+
+fn assert_marketing(storage: &dyn Storage, sender: &Addr) -> Result<(), ContractError> {
+    let marketing = MARKETING_INFO
+        .may_load(storage)?
+        .and_then(|info| info.marketing);
+    if marketing.as_ref() == Some(sender) {
+        return Ok(());
+    }
+    Ok(cw_ownable::assert_owner(storage, sender)?)
+}
+
+fn execute_update_marketing(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    project: Option<String>,
+    description: Option<String>,
+    marketing: Option<String>,
+) -> Result<Response, ContractError> {
+    assert_marketing(deps.storage, &info.sender)?;
+
+    let mut marketing_info = MARKETING_INFO.may_load(deps.storage)?.unwrap_or_default();
+
+    match project {
+        Some(empty) if empty.trim().is_empty() => marketing_info.project = None,
+        Some(project) => marketing_info.project = Some(project),
+        None => (),
+    }
+
+    match description {
+        Some(empty) if empty.trim().is_empty() => marketing_info.description = None,
+        Some(description) => marketing_info.description = Some(description),
+        None => (),
+    }
+
+    match marketing {
+        Some(empty) if empty.trim().is_empty() => marketing_info.marketing = None,
+        Some(marketing) => marketing_info.marketing = Some(deps.api.addr_validate(&marketing)?),
+        None => (),
+    }
+
+    MARKETING_INFO.save(deps.storage, &marketing_info)?;
+
+    Ok(Response::new().add_attribute("action", "update_marketing"))
+}
+
+fn execute_upload_logo(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    logo: Logo,
+) -> Result<Response, ContractError> {
+    assert_marketing(deps.storage, &info.sender)?;
+    verify_logo(&logo)?;
+
+    LOGO.save(deps.storage, &logo)?;
+
+    let mut marketing_info = MARKETING_INFO.may_load(deps.storage)?.unwrap_or_default();
+    marketing_info.logo = Some(match logo {
+        Logo::Url(url) => LogoInfo::Url(url),
+        Logo::Embedded(_) => LogoInfo::Embedded,
+    });
+    MARKETING_INFO.save(deps.storage, &marketing_info)?;
+
+    Ok(Response::new().add_attribute("action", "upload_logo"))
+}
+
+fn execute_update_tax_config(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    bps: Option<u16>,
+    treasury: Option<String>,
+    add_exempt: Option<Vec<String>>,
+    remove_exempt: Option<Vec<String>>,
+) -> Result<Response, ContractError> {
+    cw_ownable::assert_owner(deps.storage, &info.sender)?;
+
+    let mut tax = TAX_CONFIG.may_load(deps.storage)?.unwrap_or_default();
+    if let Some(bps) = bps {
+        if bps > MAX_TAX_BPS {
+            return Err(ContractError::TaxTooHigh {
+                bps,
+                max: MAX_TAX_BPS,
+            });
+        }
+        tax.bps = bps;
+    }
+    if let Some(treasury) = treasury {
+        tax.treasury = Some(deps.api.addr_validate(&treasury)?);
+    }
+    for addr in add_exempt.unwrap_or_default() {
+        let addr = deps.api.addr_validate(&addr)?;
+        if !tax.exempt.contains(&addr) {
+            tax.exempt.push(addr);
+        }
+    }
+    for addr in remove_exempt.unwrap_or_default() {
+        let addr = deps.api.addr_validate(&addr)?;
+        tax.exempt.retain(|exempt| *exempt != addr);
+    }
+    if tax.bps > 0 && tax.treasury.is_none() {
+        return Err(ContractError::InvalidInput {});
+    }
+    TAX_CONFIG.save(deps.storage, &tax)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "update_tax_config")
+        .add_attribute("bps", tax.bps.to_string())
+        .add_attribute(
+            "treasury",
+            tax.treasury
+                .map_or_else(|| "none".to_string(), String::from),
+        ))
+}
+
+fn execute_update_collection_info(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    name: Option<String>,
+    symbol: Option<String>,
+    token_uri: Option<String>,
+) -> Result<Response, ContractError> {
+    cw_ownable::assert_owner(deps.storage, &info.sender)?;
+
+    let mut metadata = METADATA.load(deps.storage)?;
+    let mut res = Response::new().add_attribute("action", "update_collection_info");
+    if let Some(name) = name {
+        if name.trim().is_empty() {
+            return Err(ContractError::InvalidInput {});
+        }
+        res = res.add_attribute("name", name.clone());
+        metadata.name = name;
+    }
+    if let Some(symbol) = symbol {
+        if symbol.trim().is_empty() {
+            return Err(ContractError::InvalidInput {});
+        }
+        res = res.add_attribute("symbol", symbol.clone());
+        metadata.symbol = symbol;
+    }
+    if let Some(token_uri) = token_uri {
+        res = res.add_attribute("token_uri", token_uri.clone());
+        metadata.token_uri = token_uri;
+    }
+    METADATA.save(deps.storage, &metadata)?;
+
+    Ok(res)
+}
+
+const LOGO_SIZE_CAP: usize = 5 * 1024;
+
+/// Checks if data starts with XML preamble
+fn verify_xml_preamble(data: &[u8]) -> Result<(), ContractError> {
+    // the preamble runs up to the first `>`
+    let preamble = data
+        .split_inclusive(|c| *c == b'>')
+        .next()
+        .ok_or(ContractError::InvalidXmlPreamble {})?;
+
+    const PREFIX: &[u8] = b"<?xml ";
+    const POSTFIX: &[u8] = b"?>";
+
+    if !(preamble.starts_with(PREFIX) && preamble.ends_with(POSTFIX)) {
+        return Err(ContractError::InvalidXmlPreamble {});
+    }
+    Ok(())
+}
+
+fn verify_logo(logo: &Logo) -> Result<(), ContractError> {
+    const PNG_HEADER: [u8; 8] = [0x89, b'P', b'N', b'G', 0x0d, 0x0a, 0x1a, 0x0a];
+
+    match logo {
+        Logo::Url(_) => Ok(()),
+        Logo::Embedded(embedded) => {
+            let data = match embedded {
+                EmbeddedLogo::Svg(data) | EmbeddedLogo::Png(data) => data.as_slice(),
+            };
+            if data.len() > LOGO_SIZE_CAP {
+                return Err(ContractError::LogoTooBig {});
+            }
+            match embedded {
+                EmbeddedLogo::Svg(_) => verify_xml_preamble(data),
+                EmbeddedLogo::Png(_) if !data.starts_with(&PNG_HEADER) => {
+                    Err(ContractError::InvalidPngHeader {})
+                }
+                EmbeddedLogo::Png(_) => Ok(()),
+            }
+        }
+    }
+}
+
+/// NFT calls relayed by the registered mirror contract on behalf of `sender`,
+/// the account that called the mirror.
+fn execute_mirror(
     deps: DepsMut,
     _env: Env,
     info: MessageInfo,
+    sender: String,
+    msg: MirrorMsg,
+) -> Result<Response, ContractError> {
+    if MIRROR.may_load(deps.storage)? != Some(info.sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+    let sender = deps.api.addr_validate(&sender)?;
+
+    match msg {
+        MirrorMsg::TransferNft {
+            recipient,
+            token_id,
+        } => {
+            let id: u128 = token_id.parse()?;
+            let owner = OWNER_OF.load(deps.storage, id)?;
+            if !can_send_nft(deps.storage, &owner, &sender, id)? {
+                return Err(ContractError::Unauthorized {});
+            }
+
+            let events = _transfer_nft(
+                deps.storage,
+                owner.clone(),
+                deps.api.addr_validate(&recipient)?,
+                token_id.clone(),
+            )?;
+
+            Ok(Response::new()
+                .add_events(events)
+                .add_attribute("action", "transfer_nft")
+                .add_attribute("sender", sender)
+                .add_attribute("owner", owner)
+                .add_attribute("recipient", recipient)
+                .add_attribute("token_id", token_id))
+        }
+        MirrorMsg::Approve { spender, token_id } => {
+            let id: u128 = token_id.parse()?;
+            let owner = OWNER_OF.load(deps.storage, id)?;
+            if !can_approve_nft(deps.storage, &owner, &sender)? {
+                return Err(ContractError::Unauthorized {});
+            }
+
+            GET_APPROVED.save(deps.storage, id, &deps.api.addr_validate(&spender)?)?;
+
+            Ok(Response::new()
+                .add_attribute("action", "approve")
+                .add_attribute("sender", sender)
+                .add_attribute("spender", spender)
+                .add_attribute("token_id", token_id))
+        }
+        MirrorMsg::Revoke { spender, token_id } => {
+            let id: u128 = token_id.parse()?;
+            let owner = OWNER_OF.load(deps.storage, id)?;
+            if !can_approve_nft(deps.storage, &owner, &sender)? {
+                return Err(ContractError::Unauthorized {});
+            }
+
+            if GET_APPROVED.may_load(deps.storage, id)? == Some(deps.api.addr_validate(&spender)?) {
+                GET_APPROVED.remove(deps.storage, id);
+            }
+
+            Ok(Response::new()
+                .add_attribute("action", "revoke")
+                .add_attribute("sender", sender)
+                .add_attribute("spender", spender)
+                .add_attribute("token_id", token_id))
+        }
+        MirrorMsg::ApproveAll { operator } => {
+            let operator_addr = deps.api.addr_validate(&operator)?;
+            IS_APPROVED_FOR_ALL.save(deps.storage, (sender.clone(), operator_addr), &true)?;
+
+            Ok(Response::new()
+                .add_attribute("action", "approve_all")
+                .add_attribute("sender", sender)
+                .add_attribute("operator", operator))
+        }
+        MirrorMsg::RevokeAll { operator } => {
+            let operator_addr = deps.api.addr_validate(&operator)?;
+            IS_APPROVED_FOR_ALL.remove(deps.storage, (sender.clone(), operator_addr));
+
+            Ok(Response::new()
+                .add_attribute("action", "revoke_all")
+                .add_attribute("sender", sender)
+                .add_attribute("operator", operator))
+        }
+    }
+}
+
+fn execute_transfer(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
     from: Option<String>,
     to: String,
     amount: u128,
@@ -199,10 +879,18 @@ fn execute_transfer(
     };
     let receiver = deps.api.addr_validate(&to)?;
 
-    _transfer_token(deps.storage, sender.clone(), receiver.clone(), amount)?;
+    let events = _transfer_token(
+        deps.storage,
+        &deps.querier,
+        &env.block,
+        sender.clone(),
+        receiver.clone(),
+        amount,
+    )?;
 
     Ok(Response::new()
         .add_messages(msgs)
+        .add_events(events)
         .add_attribute("action", "transfer")
         .add_attribute("sender", sender)
         .add_attribute("receiver", to))
@@ -215,20 +903,21 @@ fn execute_transfer_nft(
     recipient: String,
     token_id: String,
 ) -> Result<Response, ContractError> {
-    _transfer_nft(
+    let events = _transfer_nft(
         deps.storage,
         info.sender.clone(),
         deps.api.addr_validate(&recipient)?,
         token_id,
     )?;
     Ok(Response::new()
+        .add_events(events)
         .add_attribute("action", "transfer")
         .add_attribute("sender", info.sender.to_string())
         .add_attribute("receiver", recipient))
 }
 fn execute_send(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     contract: String,
     amount: u128,
@@ -237,13 +926,23 @@ fn execute_send(
     let sender = info.sender.clone();
     let receiver = deps.api.addr_validate(&contract)?;
 
-    _transfer_token(deps.storage, sender.clone(), receiver.clone(), amount)?;
+    // the receiving contract is told what actually arrived
+    let tax = transfer_tax(deps.storage, &sender, &receiver, amount)?.map_or(0, |(_, tax)| tax);
+    let events = _transfer_token(
+        deps.storage,
+        &deps.querier,
+        &env.block,
+        sender.clone(),
+        receiver.clone(),
+        amount,
+    )?;
 
     Ok(Response::new()
+        .add_events(events)
         .add_message(
             Cw20ReceiveMsg {
                 sender: info.sender.to_string(),
-                amount: Uint128::new(amount),
+                amount: Uint128::new(amount - tax),
                 msg,
             }
             .into_cosmos_msg(contract)?,
@@ -264,25 +963,213 @@ fn execute_send_nft(
     let sender = info.sender.clone();
     let receiver = deps.api.addr_validate(&contract)?;
 
-    _transfer_nft(
+    let events = _transfer_nft(
+        deps.storage,
+        sender.clone(),
+        receiver.clone(),
+        token_id.clone(),
+    )?;
+
+    Ok(Response::new()
+        .add_events(events)
+        .add_message(
+            Cw721ReceiveMsg {
+                sender: info.sender.to_string(),
+                token_id,
+                msg,
+            }
+            .into_cosmos_msg(contract)?,
+        )
+        .add_attribute("action", "send_nft")
+        .add_attribute("sender", sender)
+        .add_attribute("receiver", receiver))
+}
+
+/// Transfers every id in order; with a `msg` the recipient is notified once
+/// with all of them.
+fn execute_batch_transfer_nft(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    recipient: String,
+    token_ids: Vec<String>,
+    msg: Option<Binary>,
+) -> Result<Response, ContractError> {
+    check_batch_size(token_ids.len())?;
+    let receiver = deps.api.addr_validate(&recipient)?;
+
+    let mut res = Response::new();
+    for token_id in &token_ids {
+        res = res.add_events(_transfer_nft(
+            deps.storage,
+            info.sender.clone(),
+            receiver.clone(),
+            token_id.clone(),
+        )?);
+    }
+
+    let action = match msg {
+        Some(msg) => {
+            res = res.add_message(
+                BatchReceiveNftMsg {
+                    sender: info.sender.to_string(),
+                    token_ids: token_ids.clone(),
+                    msg,
+                }
+                .into_cosmos_msg(recipient)?,
+            );
+            "batch_send_nft"
+        }
+        None => "batch_transfer_nft",
+    };
+
+    Ok(res
+        .add_attribute("action", action)
+        .add_attribute("sender", info.sender)
+        .add_attribute("receiver", receiver)
+        .add_attribute("token_ids", token_ids.join(",")))
+}
+
+fn execute_batch_airdrop(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    recipients: Vec<(String, u128)>,
+) -> Result<Response, ContractError> {
+    assert_role(deps.storage, &info.sender, Role::AirdropOperator)?;
+    check_batch_size(recipients.len())?;
+
+    let unit = get_unit(deps.storage)?;
+    let mut res = Response::new();
+    let mut total = Uint128::zero();
+    for (recipient, nft_amount) in recipients.iter() {
+        let amount = Uint128::new(*nft_amount).checked_mul(unit)?;
+        res = res.add_events(_transfer_token(
+            deps.storage,
+            &deps.querier,
+            &env.block,
+            env.contract.address.clone(),
+            deps.api.addr_validate(recipient)?,
+            amount.u128(),
+        )?);
+        total = total.checked_add(amount)?;
+    }
+
+    Ok(res
+        .add_attribute("action", "batch_airdrop")
+        .add_attribute("recipients", recipients.len().to_string())
+        .add_attribute("amount", total))
+}
+
+fn check_batch_size(size: usize) -> Result<(), ContractError> {
+    if size == 0 {
+        return Err(ContractError::InvalidInput {});
+    }
+    if size > MAX_BATCH {
+        return Err(ContractError::BatchTooLarge {
+            size,
+            max: MAX_BATCH,
+        });
+    }
+    Ok(())
+}
+
+/// Creates `amount` of new supply for `recipient`, backed by an underlying
+/// deposit the caller has already checked.
+fn execute_wrap(
+    deps: DepsMut,
+    env: Env,
+    recipient: Addr,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    let mut metadata = METADATA.load(deps.storage)?;
+    metadata.total_supply = Uint128::new(metadata.total_supply)
+        .checked_add(amount)?
+        .u128();
+    METADATA.save(deps.storage, &metadata)?;
+
+    // the new supply passes through the contract so NFTs are minted the same
+    // way as for any other incoming transfer
+    let contract_balance = BALANCE_OF
+        .may_load(deps.storage, env.contract.address.clone())?
+        .unwrap_or_default();
+    save_balance(
+        deps.storage,
+        &env.contract.address,
+        contract_balance.checked_add(amount)?,
+    )?;
+    let events = _transfer_token(
+        deps.storage,
+        &deps.querier,
+        &env.block,
+        env.contract.address.clone(),
+        recipient.clone(),
+        amount.u128(),
+    )?;
+
+    Ok(Response::new()
+        .add_events(events)
+        .add_attribute("action", "wrap")
+        .add_attribute("recipient", recipient)
+        .add_attribute("amount", amount))
+}
+
+fn execute_unwrap(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    amount: u128,
+) -> Result<Response, ContractError> {
+    let underlying = UNDERLYING
+        .may_load(deps.storage)?
+        .ok_or(ContractError::NotWrapper {})?;
+    nonpayable(&info)?;
+    if amount == 0 {
+        return Err(ContractError::InvalidInput {});
+    }
+
+    let events = _transfer_token(
         deps.storage,
-        sender.clone(),
-        receiver.clone(),
-        token_id.clone(),
+        &deps.querier,
+        &env.block,
+        info.sender.clone(),
+        env.contract.address.clone(),
+        amount,
+    )?;
+
+    let amount = Uint128::new(amount);
+    let contract_balance = BALANCE_OF
+        .may_load(deps.storage, env.contract.address.clone())?
+        .unwrap_or_default();
+    save_balance(
+        deps.storage,
+        &env.contract.address,
+        contract_balance.checked_sub(amount)?,
     )?;
+    let mut metadata = METADATA.load(deps.storage)?;
+    metadata.total_supply = Uint128::new(metadata.total_supply)
+        .checked_sub(amount)?
+        .u128();
+    METADATA.save(deps.storage, &metadata)?;
+
+    let release: CosmosMsg = match underlying {
+        Denom::Native(denom) => BankMsg::Send {
+            to_address: info.sender.to_string(),
+            amount: coins(amount.u128(), denom),
+        }
+        .into(),
+        Denom::Cw20(addr) => Cw20Contract(addr).call(Cw20ExecuteMsg::Transfer {
+            recipient: info.sender.to_string(),
+            amount,
+        })?,
+    };
 
     Ok(Response::new()
-        .add_message(
-            Cw721ReceiveMsg {
-                sender: info.sender.to_string(),
-                token_id,
-                msg,
-            }
-            .into_cosmos_msg(contract)?,
-        )
-        .add_attribute("action", "send_nft")
-        .add_attribute("sender", sender)
-        .add_attribute("receiver", receiver))
+        .add_message(release)
+        .add_events(events)
+        .add_attribute("action", "unwrap")
+        .add_attribute("sender", info.sender)
+        .add_attribute("amount", amount))
 }
 
 fn execute_increase_allowance(
@@ -312,7 +1199,7 @@ fn execute_increase_allowance(
 
 fn execute_transfer_from(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     from: String,
     to: String,
@@ -336,9 +1223,17 @@ fn execute_transfer_from(
         &allowance.checked_sub(Uint128::new(amount))?,
     )?;
 
-    _transfer_token(deps.storage, from_addr.clone(), to_addr.clone(), amount)?;
+    let events = _transfer_token(
+        deps.storage,
+        &deps.querier,
+        &env.block,
+        from_addr.clone(),
+        to_addr.clone(),
+        amount,
+    )?;
 
     Ok(Response::new()
+        .add_events(events)
         .add_attribute("action", "transfer_from")
         .add_attribute("from", from)
         .add_attribute("to", to)
@@ -393,7 +1288,7 @@ fn execute_set_approval_for_all(
         .add_attribute("approved", approved.to_string()))
 }
 
-fn _mint(storage: &mut dyn Storage, to: Addr) -> Result<(), ContractError> {
+fn _mint(storage: &mut dyn Storage, block: &BlockInfo, to: Addr) -> Result<u128, ContractError> {
     let minted = MINTED.load(storage)?;
     MINTED.save(storage, &(minted + 1))?;
 
@@ -404,6 +1299,7 @@ fn _mint(storage: &mut dyn Storage, to: Addr) -> Result<(), ContractError> {
     };
 
     OWNER_OF.save(storage, id, &to)?;
+    TOKEN_ENTROPY.save(storage, id, &token_entropy(block, id))?;
 
     let mut owned_ids = OWNED.load(storage, to.clone()).unwrap_or(vec![]);
     owned_ids.push(id);
@@ -411,71 +1307,160 @@ fn _mint(storage: &mut dyn Storage, to: Addr) -> Result<(), ContractError> {
 
     OWNED_INDEX.save(storage, id, &(owned_ids.len() as u128 - 1))?;
 
-    Ok(())
+    let live = LIVE_NFTS.may_load(storage)?.unwrap_or_default();
+    LIVE_NFTS.save(storage, &(live + 1))?;
+
+    Ok(id)
 }
 
-fn _burn(storage: &mut dyn Storage, from: Addr) -> Result<(), ContractError> {
+/// Burns the most recently received unlocked NFT of `from` and returns its id.
+/// Fails when every NFT it holds is locked.
+fn _burn(storage: &mut dyn Storage, from: Addr) -> Result<u128, ContractError> {
     let mut owned = OWNED.load(storage, from.clone())?;
 
-    let id = owned[owned.len() - 1];
+    let mut index = None;
+    for (i, id) in owned.iter().enumerate().rev() {
+        if !LOCKED.has(storage, *id) {
+            index = Some(i);
+            break;
+        }
+    }
+    let index = index.ok_or(ContractError::TokenLocked {})?;
 
-    owned.pop();
+    let id = owned.swap_remove(index);
+    if let Some(moved) = owned.get(index) {
+        OWNED_INDEX.save(storage, *moved, &(index as u128))?;
+    }
     OWNED.save(storage, from, &owned)?;
 
     OWNED_INDEX.remove(storage, id);
     OWNER_OF.remove(storage, id);
     GET_APPROVED.remove(storage, id);
+    TOKEN_ENTROPY.remove(storage, id);
 
-    Ok(())
+    let live = LIVE_NFTS.may_load(storage)?.unwrap_or_default();
+    LIVE_NFTS.save(storage, &live.saturating_sub(1))?;
+
+    Ok(id)
 }
 
+/// Moves `amount` from `sender`, of which the receiver gets what is left
+/// after the transfer tax. The tax is split off first so each side's NFTs
+/// are reconciled against its final balance.
 fn _transfer_token(
     storage: &mut dyn Storage,
+    querier: &QuerierWrapper,
+    block: &BlockInfo,
     sender: Addr,
     receiver: Addr,
     amount: u128,
-) -> Result<(), ContractError> {
+) -> Result<Vec<Event>, ContractError> {
+    let tax = transfer_tax(storage, &sender, &receiver, amount)?;
+    let net = amount - tax.as_ref().map_or(0, |(_, tax)| *tax);
+
+    let mut events = _move_balance(storage, querier, block, sender.clone(), receiver, net)?;
+    if let Some((treasury, tax)) = tax {
+        events.extend(_move_balance(
+            storage,
+            querier,
+            block,
+            sender,
+            treasury.clone(),
+            tax,
+        )?);
+        events.push(
+            Event::new("tax")
+                .add_attribute("treasury", treasury)
+                .add_attribute("amount", tax.to_string()),
+        );
+    }
+    Ok(events)
+}
+
+/// The tax owed on a transfer and where it goes, `None` when exempt.
+fn transfer_tax(
+    storage: &dyn Storage,
+    sender: &Addr,
+    receiver: &Addr,
+    amount: u128,
+) -> StdResult<Option<(Addr, u128)>> {
+    let config = match TAX_CONFIG.may_load(storage)? {
+        Some(config) if config.bps > 0 => config,
+        _ => return Ok(None),
+    };
+    let treasury = match config.treasury {
+        Some(treasury) => treasury,
+        None => return Ok(None),
+    };
+    if sender == receiver {
+        return Ok(None);
+    }
+    for addr in [sender, receiver] {
+        // the contract itself is always whitelisted
+        if config.exempt.contains(addr)
+            || WHITELIST.may_load(storage, addr.clone())?.unwrap_or(false)
+        {
+            return Ok(None);
+        }
+    }
+
+    let tax = Uint128::new(amount)
+        .multiply_ratio(config.bps, 10_000u128)
+        .u128();
+    Ok((tax > 0).then_some((treasury, tax)))
+}
+
+fn _move_balance(
+    storage: &mut dyn Storage,
+    querier: &QuerierWrapper,
+    block: &BlockInfo,
+    sender: Addr,
+    receiver: Addr,
+    amount: u128,
+) -> Result<Vec<Event>, ContractError> {
     let unit = get_unit(storage)?;
 
     let balance_before_sender = BALANCE_OF
         .load(storage, sender.clone())
         .unwrap_or(Uint128::zero());
+    // a self transfer moves nothing; the two writes below would mint it
+    if sender == receiver {
+        balance_before_sender.checked_sub(Uint128::new(amount))?;
+        return Ok(vec![transfer_event(
+            &sender,
+            &receiver,
+            Uint128::new(amount),
+        )]);
+    }
     let balance_before_receiver = BALANCE_OF
         .load(storage, receiver.clone())
         .unwrap_or(Uint128::zero());
 
-    BALANCE_OF.save(
-        storage,
-        sender.clone(),
-        &(balance_before_sender - Uint128::new(amount)),
-    )?;
-    BALANCE_OF.save(
-        storage,
-        receiver.clone(),
-        &(balance_before_receiver + Uint128::new(amount)),
-    )?;
-
-    if !WHITELIST.load(storage, sender.clone()).unwrap_or(false) {
-        let tokens_to_burn = balance_before_sender.checked_div(unit)?
-            - BALANCE_OF
-                .load(storage, sender.clone())?
-                .checked_div(unit)?;
-        for _ in 0..tokens_to_burn.u128() {
-            _burn(storage, sender.clone())?;
-        }
+    let sender_balance = balance_before_sender.checked_sub(Uint128::new(amount))?;
+    let receiver_balance = balance_before_receiver.checked_add(Uint128::new(amount))?;
+    save_balance(storage, &sender, sender_balance)?;
+    save_balance(storage, &receiver, receiver_balance)?;
+
+    // the sender never keeps more NFTs than its balance backs, even when it
+    // skips NFTs and holds some it received explicitly
+    let mut burned = vec![];
+    let backed = sender_balance.checked_div(unit)?.u128();
+    for _ in backed..owned_count(storage, &sender)? {
+        burned.push(_burn(storage, sender.clone())?);
     }
 
-    if !WHITELIST.load(storage, receiver.clone()).unwrap_or(false) {
-        let tokens_to_mint = BALANCE_OF
-            .load(storage, receiver.clone())?
-            .checked_div(unit)?
-            - balance_before_receiver.checked_div(unit)?;
-        for _ in 0..tokens_to_mint.u128() {
-            _mint(storage, receiver.clone())?;
+    let mut minted = vec![];
+    if !is_skip_nft(storage, querier, &receiver)? {
+        let backed = receiver_balance.checked_div(unit)?.u128();
+        for _ in owned_count(storage, &receiver)?..backed {
+            minted.push(_mint(storage, block, receiver.clone())?);
         }
     }
 
-    Ok(())
+    let mut events = vec![transfer_event(&sender, &receiver, Uint128::new(amount))];
+    events.extend(nft_burn_event(&sender, &burned));
+    events.extend(nft_mint_event(&receiver, &minted));
+    Ok(events)
 }
 
 fn _transfer_nft(
@@ -483,7 +1468,7 @@ fn _transfer_nft(
     sender: Addr,
     receiver: Addr,
     token_id: String,
-) -> Result<(), ContractError> {
+) -> Result<Vec<Event>, ContractError> {
     let id: u128 = token_id.parse()?;
 
     let unit = get_unit(storage)?;
@@ -491,6 +1476,10 @@ fn _transfer_nft(
     if sender != OWNER_OF.load(storage, id)? {
         return Err(ContractError::InvalidSender {});
     }
+    // the balance and index moves below assume two distinct holders
+    if sender == receiver {
+        return Err(ContractError::InvalidInput {});
+    }
 
     // if !IS_APPROVED_FOR_ALL
     //         .load(deps.storage, (sender.clone(), Addr::unchecked(from)))
@@ -507,20 +1496,14 @@ fn _transfer_nft(
         .load(storage, receiver.clone())
         .unwrap_or(Uint128::zero());
 
-    BALANCE_OF.save(
-        storage,
-        sender.clone(),
-        &(sender_balance_of.checked_sub(unit)?),
-    )?;
-    BALANCE_OF.save(
-        storage,
-        receiver.clone(),
-        &(receiver_balance_of.checked_add(unit)?),
-    )?;
+    save_balance(storage, &sender, sender_balance_of.checked_sub(unit)?)?;
+    save_balance(storage, &receiver, receiver_balance_of.checked_add(unit)?)?;
 
     OWNER_OF.save(storage, id, &receiver)?;
 
     GET_APPROVED.remove(storage, id);
+    // a lock is the owner's pin, it does not travel with the token
+    LOCKED.remove(storage, id);
 
     let mut sender_owned = OWNED.load(storage, sender.clone()).unwrap_or(vec![]);
     let mut receiver_owned = OWNED.load(storage, receiver.clone()).unwrap_or(vec![]);
@@ -540,80 +1523,594 @@ fn _transfer_nft(
 
     OWNED_INDEX.save(storage, id, &(receiver_owned.len() as u128 - 1))?;
 
+    Ok(vec![
+        transfer_event(&sender, &receiver, unit),
+        Event::new("nft_transfer")
+            .add_attribute("from", sender)
+            .add_attribute("to", receiver)
+            .add_attribute("token_id", token_id),
+    ])
+}
+
+/// The owner can act in every role; a role holder only in its own.
+fn assert_role(storage: &dyn Storage, sender: &Addr, role: Role) -> Result<(), ContractError> {
+    if cw_ownable::is_owner(storage, sender)? {
+        return Ok(());
+    }
+
+    let roles = ROLES.may_load(storage)?.unwrap_or_default();
+    let holder = match role {
+        Role::WhitelistManager => roles.whitelist_manager,
+        Role::AirdropOperator => roles.airdrop_operator,
+    };
+    if holder.as_ref() != Some(sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+
     Ok(())
 }
 
-fn get_unit(storage: &dyn Storage) -> Result<Uint128, ContractError> {
-    let metadata = METADATA.load(storage)?;
-    Ok(Uint128::new(
-        (10u32).pow(metadata.decimals as u32 + 1) as u128
-    ))
+fn can_approve_nft(storage: &dyn Storage, owner: &Addr, sender: &Addr) -> StdResult<bool> {
+    Ok(sender == owner
+        || IS_APPROVED_FOR_ALL
+            .may_load(storage, (owner.clone(), sender.clone()))?
+            .unwrap_or(false))
+}
+
+fn can_send_nft(storage: &dyn Storage, owner: &Addr, sender: &Addr, id: u128) -> StdResult<bool> {
+    Ok(can_approve_nft(storage, owner, sender)?
+        || GET_APPROVED.may_load(storage, id)?.as_ref() == Some(sender))
+}
+
+/// Whether `addr` holds its balance without NFT mirroring. The admin
+/// whitelist always wins; otherwise the holder's own choice applies, falling
+/// back to skipping for contract addresses.
+fn is_skip_nft(storage: &dyn Storage, querier: &QuerierWrapper, addr: &Addr) -> StdResult<bool> {
+    if WHITELIST.may_load(storage, addr.clone())?.unwrap_or(false) {
+        return Ok(true);
+    }
+    match SKIP_NFT.may_load(storage, addr.clone())? {
+        Some(skip) => Ok(skip),
+        None => Ok(querier.query_wasm_contract_info(addr).is_ok()),
+    }
+}
+
+/// Mints or burns NFTs until `owner` holds exactly as many as its balance
+/// covers. A holder that skips NFTs keeps whatever it already holds until it
+/// opts in again, and locked NFTs are never burned.
+fn _sync_nfts(
+    storage: &mut dyn Storage,
+    querier: &QuerierWrapper,
+    block: &BlockInfo,
+    owner: Addr,
+) -> Result<Vec<Event>, ContractError> {
+    if is_skip_nft(storage, querier, &owner)? {
+        return Ok(vec![]);
+    }
+    let balance = BALANCE_OF
+        .may_load(storage, owner.clone())?
+        .unwrap_or_default();
+    let target = balance.checked_div(get_unit(storage)?)?.u128();
+    let owned = owned_count(storage, &owner)?;
+
+    let mut burned = vec![];
+    for _ in target..owned {
+        match _burn(storage, owner.clone()) {
+            Ok(id) => burned.push(id),
+            Err(ContractError::TokenLocked {}) => break,
+            Err(err) => return Err(err),
+        }
+    }
+    let mut minted = vec![];
+    for _ in owned..target {
+        minted.push(_mint(storage, block, owner.clone())?);
+    }
+
+    Ok(nft_burn_event(&owner, &burned)
+        .into_iter()
+        .chain(nft_mint_event(&owner, &minted))
+        .collect())
+}
+
+/// Burns every NFT of `owner` that is not locked.
+fn _burn_unlocked(storage: &mut dyn Storage, owner: Addr) -> Result<Vec<Event>, ContractError> {
+    let mut burned = vec![];
+    for _ in 0..owned_count(storage, &owner)? {
+        match _burn(storage, owner.clone()) {
+            Ok(id) => burned.push(id),
+            Err(ContractError::TokenLocked {}) => break,
+            Err(err) => return Err(err),
+        }
+    }
+    Ok(nft_burn_event(&owner, &burned).into_iter().collect())
+}
+
+fn owned_count(storage: &dyn Storage, owner: &Addr) -> StdResult<u128> {
+    Ok(OWNED
+        .may_load(storage, owner.clone())?
+        .map_or(0, |owned| owned.len() as u128))
+}
+
+/// Fungible movement of `amount`, emitted as `wasm-transfer`.
+fn transfer_event(from: &Addr, to: &Addr, amount: Uint128) -> Event {
+    Event::new("transfer")
+        .add_attribute("from", from)
+        .add_attribute("to", to)
+        .add_attribute("amount", amount)
+}
+
+fn nft_mint_event(to: &Addr, ids: &[u128]) -> Option<Event> {
+    (!ids.is_empty()).then(|| {
+        Event::new("nft_mint")
+            .add_attribute("to", to)
+            .add_attribute("amount", ids.len().to_string())
+            .add_attribute("token_ids", join_ids(ids))
+    })
+}
+
+fn nft_burn_event(from: &Addr, ids: &[u128]) -> Option<Event> {
+    (!ids.is_empty()).then(|| {
+        Event::new("nft_burn")
+            .add_attribute("from", from)
+            .add_attribute("amount", ids.len().to_string())
+            .add_attribute("token_ids", join_ids(ids))
+    })
+}
+
+fn join_ids(ids: &[u128]) -> String {
+    ids.iter()
+        .map(|id| id.to_string())
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Writes a balance while keeping `HOLDER_COUNT` in step with it. Empty
+/// balances are removed so only holders are enumerated.
+fn save_balance(storage: &mut dyn Storage, addr: &Addr, balance: Uint128) -> StdResult<()> {
+    let held = BALANCE_OF
+        .may_load(storage, addr.clone())?
+        .is_some_and(|b| !b.is_zero());
+    if balance.is_zero() {
+        BALANCE_OF.remove(storage, addr.clone());
+    } else {
+        BALANCE_OF.save(storage, addr.clone(), &balance)?;
+    }
+
+    let count = HOLDER_COUNT.may_load(storage)?.unwrap_or_default();
+    match (held, balance.is_zero()) {
+        (false, false) => HOLDER_COUNT.save(storage, &(count + 1)),
+        (true, true) => HOLDER_COUNT.save(storage, &count.saturating_sub(1)),
+        _ => Ok(()),
+    }
+}
+
+fn get_unit(storage: &dyn Storage) -> StdResult<Uint128> {
+    Ok(METADATA.load(storage)?.units_per_nft)
+}
+
+/// NFT metadata settings. Until the admin configures them, token URIs are
+/// `{token_uri}/{id}.png` and no traits are assigned.
+fn load_nft_metadata(storage: &dyn Storage) -> StdResult<NftMetadata> {
+    match NFT_METADATA.may_load(storage)? {
+        Some(nft_metadata) => Ok(nft_metadata),
+        None => Ok(NftMetadata {
+            base_uri: format!("{}/{{id}}.png", METADATA.load(storage)?.token_uri),
+            placeholder_uri: None,
+            traits: vec![],
+            reveal_seed: None,
+        }),
+    }
+}
+
+/// Pseudo-random bytes fixed for a token when it is minted. Block data is
+/// known to validators, which is why traits only resolve after `Reveal`.
+fn token_entropy(block: &BlockInfo, id: u128) -> Binary {
+    let mut hasher = Sha256::new();
+    hasher.update(block.chain_id.as_bytes());
+    hasher.update(block.height.to_be_bytes());
+    hasher.update(block.time.nanos().to_be_bytes());
+    hasher.update(id.to_be_bytes());
+    Binary::from(hasher.finalize().to_vec())
+}
+
+/// Picks one value per trait from the reveal seed and the token's entropy.
+fn assign_traits(traits: &[TraitDef], seed: &Binary, entropy: &Binary) -> Vec<Trait> {
+    traits
+        .iter()
+        .enumerate()
+        .map(|(index, trait_def)| {
+            let mut hasher = Sha256::new();
+            hasher.update(seed.as_slice());
+            hasher.update(entropy.as_slice());
+            hasher.update((index as u32).to_be_bytes());
+            let hash = hasher.finalize();
+
+            let mut roll = [0u8; 8];
+            roll.copy_from_slice(&hash[..8]);
+            let pick = u64::from_be_bytes(roll) % trait_def.values.len() as u64;
+            Trait {
+                trait_type: trait_def.trait_type.clone(),
+                value: trait_def.values[pick as usize].clone(),
+            }
+        })
+        .collect()
+}
+
+/// Claim policy for `Mint {}`. Until the admin configures one, any address
+/// may claim one NFT's worth of tokens per day.
+fn load_claim_config(storage: &dyn Storage) -> StdResult<ClaimConfig> {
+    match CLAIM_CONFIG.may_load(storage)? {
+        Some(claim) => Ok(claim),
+        None => Ok(ClaimConfig {
+            amount: get_unit(storage)?,
+            cooldown: 86_400,
+            per_address_cap: None,
+            global_cap: None,
+            start_time: None,
+            end_time: None,
+            fee: None,
+            treasury: None,
+            merkle_root: None,
+        }),
+    }
+}
+
+/// Checks `claimer` against a sha256 merkle tree whose leaves are hashed
+/// addresses and whose pairs are sorted before hashing.
+fn verify_claim_proof(
+    merkle_root: &str,
+    claimer: &Addr,
+    proof: Vec<String>,
+) -> Result<(), ContractError> {
+    let leaf: [u8; 32] = Sha256::digest(claimer.as_bytes()).into();
+    let hash = proof.into_iter().try_fold(leaf, |hash, p| {
+        let mut proof_buf: [u8; 32] = [0; 32];
+        hex::decode_to_slice(p, &mut proof_buf)?;
+        let mut hashes = [hash, proof_buf];
+        hashes.sort_unstable();
+        Ok::<[u8; 32], ContractError>(Sha256::digest(hashes.concat()).into())
+    })?;
+
+    let mut root_buf: [u8; 32] = [0; 32];
+    hex::decode_to_slice(merkle_root, &mut root_buf)?;
+    if root_buf != hash {
+        return Err(ContractError::InvalidProof {});
+    }
+
+    Ok(())
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn migrate(deps: DepsMut, env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
-    let new_total_supply = 100000000000000u128;
+pub fn migrate(deps: DepsMut, env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
+    let stored = get_contract_version(deps.storage)?;
+    if stored.contract != CONTRACT_NAME
+        || stored.version.parse::<Version>()? > CONTRACT_VERSION.parse::<Version>()?
+    {
+        return Err(ContractError::CannotMigrate {
+            contract: stored.contract,
+            version: stored.version,
+        });
+    }
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    // the admin of older versions becomes the cw-ownable owner
+    if let Some(config) = CONFIG.may_load(deps.storage)? {
+        cw_ownable::initialize_owner(deps.storage, deps.api, Some(config.admin.as_str()))?;
+        ROLES.save(deps.storage, &Roles::default())?;
+        CONFIG.remove(deps.storage);
+    }
+
+    // counters introduced after the first release start from a full scan
+    if HOLDER_COUNT.may_load(deps.storage)?.is_none() {
+        let holders = BALANCE_OF
+            .range(deps.storage, None, None, Order::Ascending)
+            .filter(|entry| entry.as_ref().map_or(true, |(_, b)| !b.is_zero()))
+            .count();
+        HOLDER_COUNT.save(deps.storage, &(holders as u64))?;
+    }
+    if LIVE_NFTS.may_load(deps.storage)?.is_none() {
+        let live = OWNER_OF
+            .keys(deps.storage, None, None, Order::Ascending)
+            .count();
+        LIVE_NFTS.save(deps.storage, &(live as u128))?;
+    }
+
+    let mut res = Response::new()
+        .add_attribute("action", "migrate")
+        .add_attribute("from_version", stored.version)
+        .add_attribute("to_version", CONTRACT_VERSION);
+
     let mut metadata: Metadata = METADATA.load(deps.storage)?;
 
-    // orig total supply - balance of
-    let balance = BALANCE_OF.load(deps.storage, env.contract.address.clone())?;
-    let used = Uint128::new(metadata.total_supply) - balance;
-    BALANCE_OF.save(
-        deps.storage,
-        env.contract.address.clone(),
-        &(Uint128::new(new_total_supply) - used),
-    )?;
+    // contracts instantiated before the unit was configurable used 10^(decimals + 1)
+    if metadata.units_per_nft.is_zero() {
+        metadata.units_per_nft = Uint128::new(10).checked_pow(metadata.decimals as u32 + 1)?;
+        res = res.add_attribute("units_per_nft", metadata.units_per_nft);
+    }
+
+    if let Some(new_total_supply) = msg.total_supply {
+        // a wrapped supply only changes through deposits and withdrawals
+        if UNDERLYING.may_load(deps.storage)?.is_some() {
+            return Err(ContractError::InvalidInput {});
+        }
+        // supply outside the contract is held by users and cannot be taken back
+        let balance = BALANCE_OF
+            .may_load(deps.storage, env.contract.address.clone())?
+            .unwrap_or_default();
+        let circulating = Uint128::new(metadata.total_supply).checked_sub(balance)?;
+        if new_total_supply < circulating {
+            return Err(ContractError::InsufficientContractBalance {
+                circulating,
+                requested: new_total_supply,
+            });
+        }
+        save_balance(
+            deps.storage,
+            &env.contract.address,
+            new_total_supply - circulating,
+        )?;
+
+        res = res
+            .add_attribute("old_total_supply", metadata.total_supply.to_string())
+            .add_attribute("new_total_supply", new_total_supply);
+        metadata.total_supply = new_total_supply.u128();
+    }
+
+    if let Some(token_uri) = msg.token_uri {
+        res = res
+            .add_attribute("old_token_uri", metadata.token_uri)
+            .add_attribute("new_token_uri", token_uri.clone());
+        metadata.token_uri = token_uri;
+    }
+
+    // holders are brought in line with the new unit page by page via Resync
+    if let Some(units_per_nft) = msg.units_per_nft {
+        if units_per_nft.is_zero() {
+            return Err(ContractError::InvalidUnitsPerNft {});
+        }
+        if units_per_nft != metadata.units_per_nft {
+            res = res
+                .add_attribute("old_units_per_nft", metadata.units_per_nft)
+                .add_attribute("new_units_per_nft", units_per_nft);
+            metadata.units_per_nft = units_per_nft;
+        }
+    }
 
-    metadata.total_supply = new_total_supply;
     METADATA.save(deps.storage, &metadata)?;
 
-    Ok(Response::new())
+    Ok(res)
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::Config {} => query_config(deps, env),
+        QueryMsg::Ownership {} => to_json_binary(&cw_ownable::get_ownership(deps.storage)?),
+        QueryMsg::Roles {} => to_json_binary(&ROLES.may_load(deps.storage)?.unwrap_or_default()),
         QueryMsg::Balance { address } => query_balance(deps, env, address),
         QueryMsg::TokenInfo {} => query_token_info(deps, env),
         QueryMsg::Minter {} => query_minter(deps, env),
+        QueryMsg::AllAccounts { start_after, limit } => {
+            query_all_accounts(deps, env, start_after, limit)
+        }
+        QueryMsg::HolderCount {} => {
+            to_json_binary(&HOLDER_COUNT.may_load(deps.storage)?.unwrap_or_default())
+        }
+        QueryMsg::Whitelisted { start_after, limit } => {
+            query_whitelisted(deps, env, start_after, limit)
+        }
+        QueryMsg::SupplyStats {} => query_supply_stats(deps, env),
+        QueryMsg::Reserve {} => query_reserve(deps, env),
+        QueryMsg::TaxConfig {} => {
+            to_json_binary(&TAX_CONFIG.may_load(deps.storage)?.unwrap_or_default())
+        }
+        QueryMsg::MarketingInfo {} => {
+            to_json_binary(&MARKETING_INFO.may_load(deps.storage)?.unwrap_or_default())
+        }
+        QueryMsg::DownloadLogo {} => query_download_logo(deps, env),
+        QueryMsg::ContractInfo {} => {
+            let metadata = METADATA.load(deps.storage)?;
+            to_json_binary(&ContractInfoResponse {
+                name: metadata.name,
+                symbol: metadata.symbol,
+            })
+        }
+        QueryMsg::SkipNft { address } => query_skip_nft(deps, env, address),
+        QueryMsg::DualityInfo {} => query_duality_info(deps, env),
+        QueryMsg::ClaimConfig {} => to_json_binary(&load_claim_config(deps.storage)?),
+        QueryMsg::ClaimStatus { address } => query_claim_status(deps, env, address),
+        QueryMsg::NftMetadata {} => to_json_binary(&load_nft_metadata(deps.storage)?),
+        QueryMsg::Mirror {} => to_json_binary(&MIRROR.may_load(deps.storage)?),
+        QueryMsg::GetApproved { token_id } => query_get_approved(deps, env, token_id),
+        QueryMsg::IsApprovedForAll { owner, operator } => {
+            query_is_approved_for_all(deps, env, owner, operator)
+        }
+        QueryMsg::AllTokens { start_after, limit } => {
+            query_all_tokens(deps, env, start_after, limit)
+        }
         QueryMsg::OwnerOf { token_id } => query_owner_of(deps, env, token_id),
         QueryMsg::Tokens { owner } => query_owned(deps, env, owner),
+        QueryMsg::LockedTokens { owner } => query_locked_tokens(deps, env, owner),
+        QueryMsg::VerifyTokens { start_after, limit } => {
+            query_verify_tokens(deps, env, start_after, limit)
+        }
+        QueryMsg::VerifyAccounting { start_after, limit } => {
+            query_verify_accounting(deps, env, start_after, limit)
+        }
         QueryMsg::NumTokens {} => query_num_tokens(deps, env),
         QueryMsg::NftInfo { token_id } => query_nft_info(deps, env, token_id),
     }
 }
 
 fn query_config(deps: Deps, _env: Env) -> StdResult<Binary> {
-    let config = CONFIG.load(deps.storage)?;
+    let ownership = cw_ownable::get_ownership(deps.storage)?;
     to_json_binary(&ConfigResponse {
-        admin: config.admin.to_string(),
+        admin: ownership.owner.map(String::from).unwrap_or_default(),
     })
 }
 
 fn query_balance(deps: Deps, _env: Env, address: String) -> StdResult<Binary> {
     let addr = deps.api.addr_validate(&address)?;
-    let balance = BALANCE_OF.load(deps.storage, addr)?;
-    Ok(to_json_binary(&BalanceResponse { balance })?)
+    let balance = BALANCE_OF.may_load(deps.storage, addr)?.unwrap_or_default();
+    to_json_binary(&BalanceResponse { balance })
+}
+
+fn query_all_accounts(
+    deps: Deps,
+    _env: Env,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<Binary> {
+    let start = start_after
+        .map(|addr| deps.api.addr_validate(&addr))
+        .transpose()?
+        .map(Bound::exclusive);
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+    // entries emptied before zero balances were removed are skipped
+    let accounts = BALANCE_OF
+        .range(deps.storage, start, None, Order::Ascending)
+        .filter(|entry| entry.as_ref().map_or(true, |(_, b)| !b.is_zero()))
+        .take(limit)
+        .map(|entry| entry.map(|(addr, _)| addr.into_string()))
+        .collect::<StdResult<Vec<String>>>()?;
+    to_json_binary(&AllAccountsResponse { accounts })
+}
+
+fn query_whitelisted(
+    deps: Deps,
+    _env: Env,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<Binary> {
+    let start = start_after
+        .map(|addr| deps.api.addr_validate(&addr))
+        .transpose()?
+        .map(Bound::exclusive);
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+    // revoked entries stay stored as `false`
+    let addresses = WHITELIST
+        .range(deps.storage, start, None, Order::Ascending)
+        .filter(|entry| entry.as_ref().map_or(true, |(_, state)| *state))
+        .take(limit)
+        .map(|entry| entry.map(|(addr, _)| addr.to_string()))
+        .collect::<StdResult<Vec<String>>>()?;
+    to_json_binary(&WhitelistedResponse { addresses })
+}
+
+fn query_download_logo(deps: Deps, _env: Env) -> StdResult<Binary> {
+    let (mime_type, data) = match LOGO.load(deps.storage)? {
+        Logo::Embedded(EmbeddedLogo::Svg(logo)) => ("image/svg+xml", logo),
+        Logo::Embedded(EmbeddedLogo::Png(logo)) => ("image/png", logo),
+        Logo::Url(_) => return Err(StdError::not_found("logo")),
+    };
+    to_json_binary(&DownloadLogoResponse {
+        mime_type: mime_type.to_string(),
+        data,
+    })
+}
+
+fn query_reserve(deps: Deps, env: Env) -> StdResult<Binary> {
+    let underlying = UNDERLYING
+        .may_load(deps.storage)?
+        .ok_or_else(|| StdError::generic_err("Contract is not in wrapper mode"))?;
+    let reserve = match &underlying {
+        Denom::Native(denom) => {
+            deps.querier
+                .query_balance(&env.contract.address, denom)?
+                .amount
+        }
+        Denom::Cw20(addr) => {
+            Cw20Contract(addr.clone()).balance(&deps.querier, env.contract.address)?
+        }
+    };
+    let wrapped = Uint128::new(METADATA.load(deps.storage)?.total_supply);
+
+    to_json_binary(&ReserveResponse {
+        underlying,
+        reserve,
+        wrapped,
+        fully_backed: reserve >= wrapped,
+    })
+}
+
+fn query_supply_stats(deps: Deps, env: Env) -> StdResult<Binary> {
+    let total_supply = Uint128::new(METADATA.load(deps.storage)?.total_supply);
+    let contract_balance = BALANCE_OF
+        .may_load(deps.storage, env.contract.address)?
+        .unwrap_or_default();
+    to_json_binary(&SupplyStatsResponse {
+        total_supply,
+        contract_balance,
+        circulating: total_supply.checked_sub(contract_balance)?,
+        live_nfts: Uint128::new(LIVE_NFTS.may_load(deps.storage)?.unwrap_or_default()),
+    })
 }
 
 fn query_token_info(deps: Deps, _env: Env) -> StdResult<Binary> {
     let metadata = METADATA.load(deps.storage)?;
-    Ok(to_json_binary(&TokenInfoResponse {
+    to_json_binary(&TokenInfoResponse {
         name: metadata.name,
         symbol: metadata.symbol,
         decimals: metadata.decimals,
         total_supply: Uint128::new(metadata.total_supply),
-    })?)
+    })
 }
 
 fn query_minter(deps: Deps, _env: Env) -> StdResult<Binary> {
-    let config = CONFIG.load(deps.storage)?;
+    let ownership = cw_ownable::get_ownership(deps.storage)?;
     let metadata = METADATA.load(deps.storage)?;
-    Ok(to_json_binary(&MinterResponse {
-        minter: config.admin.to_string(),
+    to_json_binary(&MinterResponse {
+        minter: ownership.owner.map(String::from).unwrap_or_default(),
         cap: Some(Uint128::new(metadata.total_supply)),
-    })?)
+    })
+}
+
+fn query_skip_nft(deps: Deps, _env: Env, address: String) -> StdResult<Binary> {
+    let addr = deps.api.addr_validate(&address)?;
+    to_json_binary(&is_skip_nft(deps.storage, &deps.querier, &addr)?)
+}
+
+fn query_duality_info(deps: Deps, _env: Env) -> StdResult<Binary> {
+    let metadata = METADATA.load(deps.storage)?;
+    let total_supply = Uint128::new(metadata.total_supply);
+    to_json_binary(&DualityInfoResponse {
+        units_per_nft: metadata.units_per_nft,
+        decimals: metadata.decimals,
+        total_supply,
+        max_nft_supply: total_supply.checked_div(metadata.units_per_nft)?,
+    })
+}
+
+fn query_claim_status(deps: Deps, env: Env, address: String) -> StdResult<Binary> {
+    let addr = deps.api.addr_validate(&address)?;
+    let claim = load_claim_config(deps.storage)?;
+
+    let mut next_claim_time = match LAST_MINT_SECONDS.may_load(deps.storage, addr.clone())? {
+        // a cooldown past the representable range just never ends
+        Some(last_mint_seconds) => Timestamp::from_nanos(
+            last_mint_seconds
+                .saturating_add(claim.cooldown)
+                .saturating_mul(1_000_000_000),
+        ),
+        None => env.block.time,
+    };
+    if let Some(start) = claim.start_time {
+        next_claim_time = next_claim_time.max(start);
+    }
+
+    let claimed = CLAIMED.may_load(deps.storage, addr)?.unwrap_or_default();
+    let total_claimed = TOTAL_CLAIMED.may_load(deps.storage)?.unwrap_or_default();
+    to_json_binary(&ClaimStatusResponse {
+        next_claim_time,
+        claimed,
+        remaining: claim.per_address_cap.map(|cap| cap.saturating_sub(claimed)),
+        global_remaining: claim
+            .global_cap
+            .map(|cap| cap.saturating_sub(total_claimed)),
+        ended: claim.end_time.is_some_and(|end| env.block.time >= end),
+    })
 }
 
 fn query_owner_of(deps: Deps, _env: Env, token_id: String) -> StdResult<Binary> {
@@ -623,25 +2120,227 @@ fn query_owner_of(deps: Deps, _env: Env, token_id: String) -> StdResult<Binary>
     }
     let id = res.unwrap();
     let owner = OWNER_OF.load(deps.storage, id)?;
-    Ok(to_json_binary(&OwnerOfResponse {
+    to_json_binary(&OwnerOfResponse {
         owner: owner.to_string(),
         approvals: vec![],
-    })?)
+    })
+}
+
+fn query_get_approved(deps: Deps, _env: Env, token_id: String) -> StdResult<Binary> {
+    let id: u128 = token_id
+        .parse()
+        .map_err(|_| StdError::generic_err("Invalid token ID"))?;
+    to_json_binary(&GET_APPROVED.may_load(deps.storage, id)?)
+}
+
+fn query_is_approved_for_all(
+    deps: Deps,
+    _env: Env,
+    owner: String,
+    operator: String,
+) -> StdResult<Binary> {
+    let owner = deps.api.addr_validate(&owner)?;
+    let operator = deps.api.addr_validate(&operator)?;
+    to_json_binary(
+        &IS_APPROVED_FOR_ALL
+            .may_load(deps.storage, (owner, operator))?
+            .unwrap_or(false),
+    )
+}
+
+fn query_all_tokens(
+    deps: Deps,
+    _env: Env,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<Binary> {
+    let start = match start_after {
+        Some(start_after) => Some(Bound::exclusive(
+            start_after
+                .parse::<u128>()
+                .map_err(|_| StdError::generic_err("Invalid token ID"))?,
+        )),
+        None => None,
+    };
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+    let tokens = OWNER_OF
+        .keys(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|id| id.map(|id| id.to_string()))
+        .collect::<StdResult<Vec<String>>>()?;
+    to_json_binary(&TokensResponse { tokens })
+}
+
+fn query_verify_accounting(
+    deps: Deps,
+    _env: Env,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<Binary> {
+    let unit = get_unit(deps.storage)?;
+    let first_page = start_after.is_none();
+    let start = start_after
+        .map(|addr| deps.api.addr_validate(&addr))
+        .transpose()?
+        .map(Bound::exclusive);
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+    // one extra entry tells whether the walk ends on this page
+    let mut holders = BALANCE_OF
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit + 1)
+        .collect::<StdResult<Vec<(Addr, Uint128)>>>()?;
+    let next = if holders.len() > limit {
+        holders.truncate(limit);
+        holders.last().map(|(holder, _)| holder.to_string())
+    } else {
+        None
+    };
+
+    let mut mismatches = vec![];
+    let mut total_balance = Uint128::zero();
+    let mut skip_held_nfts = 0;
+    for (holder, balance) in holders.iter() {
+        total_balance += *balance;
+
+        let owned = OWNED
+            .may_load(deps.storage, holder.clone())?
+            .unwrap_or_default();
+        let expected = balance.checked_div(unit)?.u128();
+        let count = owned.len() as u128;
+        let count_ok = if is_skip_nft(deps.storage, &deps.querier, holder)? {
+            skip_held_nfts += count;
+            count <= expected
+        } else {
+            count == expected
+        };
+        if !count_ok {
+            mismatches.push(AccountingMismatch::NftCount {
+                holder: holder.to_string(),
+                balance: *balance,
+                expected,
+                owned: count,
+            });
+        }
+
+        for (i, id) in owned.iter().enumerate() {
+            let owner = OWNER_OF.may_load(deps.storage, *id)?;
+            if owner.as_ref() != Some(holder) {
+                mismatches.push(AccountingMismatch::OwnerOf {
+                    holder: holder.to_string(),
+                    token_id: id.to_string(),
+                    owner: owner.map(|owner| owner.to_string()),
+                });
+            }
+            let index = OWNED_INDEX.may_load(deps.storage, *id)?;
+            if index != Some(i as u128) {
+                mismatches.push(AccountingMismatch::OwnedIndex {
+                    holder: holder.to_string(),
+                    token_id: id.to_string(),
+                    index,
+                    expected: i as u128,
+                });
+            }
+        }
+    }
+
+    let total_supply = Uint128::new(METADATA.load(deps.storage)?.total_supply);
+    if first_page && next.is_none() && total_balance != total_supply {
+        mismatches.push(AccountingMismatch::TotalSupply {
+            total_balance,
+            total_supply,
+        });
+    }
+
+    to_json_binary(&VerifyAccountingResponse {
+        checked: holders.len() as u32,
+        mismatches,
+        next,
+        page_balance: total_balance,
+        total_supply,
+        skip_held_nfts,
+    })
+}
+
+fn query_verify_tokens(
+    deps: Deps,
+    _env: Env,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<Binary> {
+    let start = start_after
+        .map(|id| {
+            id.parse::<u128>()
+                .map_err(|_| StdError::generic_err("Invalid token ID"))
+        })
+        .transpose()?
+        .map(Bound::exclusive);
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+    let mut tokens = OWNER_OF
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit + 1)
+        .collect::<StdResult<Vec<(u128, Addr)>>>()?;
+    let next = if tokens.len() > limit {
+        tokens.truncate(limit);
+        tokens.last().map(|(id, _)| id.to_string())
+    } else {
+        None
+    };
+
+    let mut mismatches = vec![];
+    let mut lists: BTreeMap<Addr, Vec<u128>> = BTreeMap::new();
+    for (id, owner) in tokens.iter() {
+        if !lists.contains_key(owner) {
+            let owned = OWNED
+                .may_load(deps.storage, owner.clone())?
+                .unwrap_or_default();
+            lists.insert(owner.clone(), owned);
+        }
+        let index = OWNED_INDEX.may_load(deps.storage, *id)?;
+        let listed = index.and_then(|index| lists[owner].get(index as usize));
+        if listed != Some(id) {
+            mismatches.push(AccountingMismatch::Owned {
+                token_id: id.to_string(),
+                owner: owner.to_string(),
+                index,
+            });
+        }
+    }
+
+    to_json_binary(&VerifyTokensResponse {
+        checked: tokens.len() as u32,
+        mismatches,
+        next,
+        live_nfts: Uint128::new(LIVE_NFTS.may_load(deps.storage)?.unwrap_or_default()),
+    })
 }
 
 fn query_owned(deps: Deps, _env: Env, address: String) -> StdResult<Binary> {
     let addr = deps.api.addr_validate(&address)?;
-    let owned = OWNED.load(deps.storage, addr)?;
-    Ok(to_json_binary(&TokensResponse {
+    let owned = OWNED.may_load(deps.storage, addr)?.unwrap_or_default();
+    to_json_binary(&TokensResponse {
         tokens: owned.iter().map(|o| o.to_string()).collect(),
-    })?)
+    })
+}
+
+fn query_locked_tokens(deps: Deps, _env: Env, address: String) -> StdResult<Binary> {
+    let addr = deps.api.addr_validate(&address)?;
+    let owned = OWNED.may_load(deps.storage, addr)?.unwrap_or_default();
+    to_json_binary(&TokensResponse {
+        tokens: owned
+            .iter()
+            .filter(|id| LOCKED.has(deps.storage, **id))
+            .map(|id| id.to_string())
+            .collect(),
+    })
 }
 
 fn query_num_tokens(deps: Deps, _env: Env) -> StdResult<Binary> {
-    let minted = MINTED.load(deps.storage)?;
-    Ok(to_json_binary(&NumTokensResponse {
-        count: minted as u64,
-    })?)
+    // burned ids are never reused, so MINTED overcounts
+    let live = LIVE_NFTS.may_load(deps.storage)?.unwrap_or_default();
+    to_json_binary(&NumTokensResponse { count: live as u64 })
 }
 
 fn query_nft_info(deps: Deps, _env: Env, token_id: String) -> StdResult<Binary> {
@@ -650,11 +2349,29 @@ fn query_nft_info(deps: Deps, _env: Env, token_id: String) -> StdResult<Binary>
         return Err(StdError::generic_err("Invalid token ID"));
     }
     let id: u128 = res.unwrap();
-    let token_uri_id = (id % 10) + 1;
-    let metadata = METADATA.load(deps.storage)?;
-    let token_uri_string = format!("{}/{}.png", metadata.token_uri, token_uri_id);
-    Ok(to_json_binary(&NftInfoResponse {
-        token_uri: Some(token_uri_string),
-        extension: Empty {},
-    })?)
+    let nft_metadata = load_nft_metadata(deps.storage)?;
+
+    let (token_uri, attributes) = match &nft_metadata.reveal_seed {
+        Some(seed) => {
+            // tokens minted before on-chain traits existed fall back to their id
+            let entropy = TOKEN_ENTROPY
+                .may_load(deps.storage, id)?
+                .unwrap_or_else(|| Binary::from(id.to_be_bytes().to_vec()));
+            (
+                nft_metadata.base_uri.replace("{id}", &token_id),
+                assign_traits(&nft_metadata.traits, seed, &entropy),
+            )
+        }
+        None => (
+            nft_metadata
+                .placeholder_uri
+                .unwrap_or_else(|| nft_metadata.base_uri.replace("{id}", &token_id)),
+            vec![],
+        ),
+    };
+
+    to_json_binary(&NftInfoResponse {
+        token_uri: Some(token_uri),
+        extension: NftExtension { attributes },
+    })
 }
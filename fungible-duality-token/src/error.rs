@@ -1,6 +1,9 @@
 use std::num::ParseIntError;
 
-use cosmwasm_std::{DivideByZeroError, OverflowError, StdError};
+use cosmwasm_std::{DivideByZeroError, OverflowError, StdError, Uint128};
+use cw_ownable::OwnershipError;
+use cw_utils::PaymentError;
+use hex::FromHexError;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -17,6 +20,18 @@ pub enum ContractError {
     #[error("{0}")]
     ParseIntError(#[from] ParseIntError),
 
+    #[error("{0}")]
+    SemVer(#[from] semver::Error),
+
+    #[error("{0}")]
+    Payment(#[from] PaymentError),
+
+    #[error("{0}")]
+    Hex(#[from] FromHexError),
+
+    #[error("{0}")]
+    Ownership(#[from] OwnershipError),
+
     #[error("Unauthorized")]
     Unauthorized {},
 
@@ -32,9 +47,57 @@ pub enum ContractError {
     #[error("Invalid funds")]
     InvalidFunds {},
 
-    #[error("Already minted today")]
-    AlreadyMintedToday {},
+    #[error("Tax of {bps} bps exceeds the maximum of {max}")]
+    TaxTooHigh { bps: u16, max: u16 },
+
+    #[error("Contract is not in wrapper mode")]
+    NotWrapper {},
+
+    #[error("Logo binary data exceeds 5KB limit")]
+    LogoTooBig {},
+
+    #[error("Invalid xml preamble for SVG")]
+    InvalidXmlPreamble {},
+
+    #[error("Invalid png header")]
+    InvalidPngHeader {},
+
+    #[error("Batch of {size} exceeds the limit of {max}")]
+    BatchTooLarge { size: usize, max: usize },
+
+    #[error("Next claim possible at {next_claim}")]
+    ClaimCooldown { next_claim: u64 },
+
+    #[error("Claiming has not started")]
+    ClaimNotStarted {},
+
+    #[error("Claiming has ended")]
+    ClaimEnded {},
+
+    #[error("Claim cap reached")]
+    ClaimCapReached {},
+
+    #[error("Invalid merkle proof")]
+    InvalidProof {},
+
+    #[error("Collection already revealed")]
+    AlreadyRevealed {},
 
     #[error("Out of supply")]
     OutOfSupply {},
+
+    #[error("Transfer would burn a locked token")]
+    TokenLocked {},
+
+    #[error("Units per NFT must be greater than zero")]
+    InvalidUnitsPerNft {},
+
+    #[error("Cannot migrate from {contract} {version}")]
+    CannotMigrate { contract: String, version: String },
+
+    #[error("Supply {requested} is below the {circulating} already held outside the contract")]
+    InsufficientContractBalance {
+        circulating: Uint128,
+        requested: Uint128,
+    },
 }
@@ -1,17 +1,34 @@
 #[cfg(test)]
 pub mod tests {
-    use crate::msg::{ExecuteMsg, InstantiateMsg, QueryMsg};
-    use cosmwasm_std::{Addr, Empty, Uint128};
-    use cw20::{BalanceResponse, TokenInfoResponse};
-    use cw721::{Cw721ExecuteMsg, Cw721QueryMsg, NumTokensResponse, OwnerOfResponse};
+    use crate::msg::{
+        ClaimConfigMsg, ClaimStatusResponse, ConfigResponse, DualityInfoResponse, ExecuteMsg,
+        InstantiateMsg, MigrateMsg, MirrorMsg, NftExtension, QueryMsg, ReserveResponse,
+        SupplyStatsResponse, VerifyAccountingResponse, VerifyTokensResponse, WhitelistedResponse,
+    };
+    use crate::state::{Role, Roles, TaxConfig, TraitDef};
+    use cosmwasm_std::{
+        coins, to_json_binary, Addr, Binary, Deps, DepsMut, Empty, Env, MessageInfo, Response,
+        StdResult, Uint128,
+    };
+    use cw20::{
+        AllAccountsResponse, BalanceResponse, Denom, DownloadLogoResponse, EmbeddedLogo, Logo,
+        LogoInfo, MarketingInfoResponse, TokenInfoResponse,
+    };
+    use cw721::{
+        ContractInfoResponse, Cw721ExecuteMsg, Cw721QueryMsg, NftInfoResponse, NumTokensResponse,
+        OwnerOfResponse, TokensResponse,
+    };
     use cw_multi_test::{App, Contract, ContractWrapper, Executor};
+    use cw_ownable::{Action, Ownership};
+    use proptest::prelude::*;
 
     pub fn challenge_contract() -> Box<dyn Contract<Empty>> {
         let contract = ContractWrapper::new(
             crate::contract::execute,
             crate::contract::instantiate,
             crate::contract::query,
-        );
+        )
+        .with_migrate(crate::contract::migrate);
 
         Box::new(contract)
     }
@@ -30,6 +47,8 @@ pub mod tests {
             decimals: 6u8,
             total_native_supply: 1,
             token_uri: "token_uri".to_string(),
+            units_per_nft: Uint128::new(1_000_000),
+            underlying: None,
         };
 
         let contract_addr = app
@@ -47,7 +66,7 @@ pub mod tests {
         app.execute_contract(
             Addr::unchecked(ADMIN),
             contract_addr.clone(),
-            &ExecuteMsg::Mint {},
+            &ExecuteMsg::Mint { proof: None },
             &[],
         )
         .unwrap();
@@ -63,7 +82,7 @@ pub mod tests {
 
     #[test]
     fn instantiate_check() {
-        let (mut app, contract_addr) = proper_instantiate();
+        let (app, contract_addr) = proper_instantiate();
 
         let balance_of: BalanceResponse = app
             .wrap()
@@ -82,6 +101,52 @@ pub mod tests {
             .unwrap();
 
         assert_eq!(token_info.total_supply, Uint128::from(1000000u128));
+
+        let duality_info: DualityInfoResponse = app
+            .wrap()
+            .query_wasm_smart(contract_addr.clone(), &QueryMsg::DualityInfo {})
+            .unwrap();
+        assert_eq!(duality_info.units_per_nft, Uint128::new(1_000_000));
+        assert_eq!(duality_info.max_nft_supply, Uint128::one());
+    }
+
+    #[test]
+    fn instantiate_rejects_invalid_units() {
+        let mut app = App::default();
+        let challenge_id = app.store_code(challenge_contract());
+
+        let inst = InstantiateMsg {
+            name: "Name".to_string(),
+            symbol: "Symbol".to_string(),
+            decimals: 18u8,
+            total_native_supply: 1_000,
+            token_uri: "token_uri".to_string(),
+            units_per_nft: Uint128::zero(),
+            underlying: None,
+        };
+        app.instantiate_contract(
+            challenge_id,
+            Addr::unchecked(ADMIN),
+            &inst,
+            &[],
+            "test",
+            None,
+        )
+        .unwrap_err();
+
+        // 18 decimals no longer overflows the supply scaling
+        app.instantiate_contract(
+            challenge_id,
+            Addr::unchecked(ADMIN),
+            &InstantiateMsg {
+                units_per_nft: Uint128::new(10u128.pow(18)),
+                ..inst
+            },
+            &[],
+            "test",
+            None,
+        )
+        .unwrap();
     }
 
     #[test]
@@ -109,4 +174,1514 @@ pub mod tests {
             .unwrap();
         assert_eq!(owner_of.owner, Addr::unchecked(USER1));
     }
+
+    #[test]
+    fn skip_nft_reconciles_holdings() {
+        let (mut app, contract_addr) = proper_instantiate();
+
+        app.execute_contract(
+            Addr::unchecked(ADMIN),
+            contract_addr.clone(),
+            &ExecuteMsg::SetSkipNft { skip: true },
+            &[],
+        )
+        .unwrap();
+        let skip: bool = app
+            .wrap()
+            .query_wasm_smart(
+                contract_addr.clone(),
+                &QueryMsg::SkipNft {
+                    address: ADMIN.to_string(),
+                },
+            )
+            .unwrap();
+        assert!(skip);
+        // skipping burns the NFTs already held
+        let tokens: TokensResponse = app
+            .wrap()
+            .query_wasm_smart(
+                contract_addr.clone(),
+                &QueryMsg::Tokens {
+                    owner: ADMIN.to_string(),
+                },
+            )
+            .unwrap();
+        assert!(tokens.tokens.is_empty());
+
+        // and moving the balance around mints none
+        app.execute_contract(
+            Addr::unchecked(ADMIN),
+            contract_addr.clone(),
+            &ExecuteMsg::Transfer {
+                recipient: USER1.to_string(),
+                amount: 1,
+            },
+            &[],
+        )
+        .unwrap();
+        app.execute_contract(
+            Addr::unchecked(USER1),
+            contract_addr.clone(),
+            &ExecuteMsg::Transfer {
+                recipient: ADMIN.to_string(),
+                amount: 1,
+            },
+            &[],
+        )
+        .unwrap();
+        let tokens: TokensResponse = app
+            .wrap()
+            .query_wasm_smart(
+                contract_addr.clone(),
+                &QueryMsg::Tokens {
+                    owner: ADMIN.to_string(),
+                },
+            )
+            .unwrap();
+        assert!(tokens.tokens.is_empty());
+
+        app.execute_contract(
+            Addr::unchecked(ADMIN),
+            contract_addr.clone(),
+            &ExecuteMsg::SetSkipNft { skip: false },
+            &[],
+        )
+        .unwrap();
+        let tokens: TokensResponse = app
+            .wrap()
+            .query_wasm_smart(
+                contract_addr.clone(),
+                &QueryMsg::Tokens {
+                    owner: ADMIN.to_string(),
+                },
+            )
+            .unwrap();
+        assert_eq!(tokens.tokens, vec!["1".to_string()]);
+    }
+
+    #[test]
+    fn whitelisting_keeps_escrowed_nft() {
+        let (mut app, contract_addr) = proper_instantiate();
+        // any contract address will do as the escrow
+        let escrow_id = app.store_code(challenge_contract());
+        let escrow = app
+            .instantiate_contract(
+                escrow_id,
+                Addr::unchecked(ADMIN),
+                &InstantiateMsg {
+                    name: "Escrow".to_string(),
+                    symbol: "ESC".to_string(),
+                    decimals: 6u8,
+                    total_native_supply: 1,
+                    token_uri: "token_uri".to_string(),
+                    units_per_nft: Uint128::new(1_000_000),
+                    underlying: None,
+                },
+                &[],
+                "escrow",
+                None,
+            )
+            .unwrap();
+
+        app.execute_contract(
+            Addr::unchecked(ADMIN),
+            contract_addr.clone(),
+            &Cw721ExecuteMsg::TransferNft {
+                recipient: escrow.to_string(),
+                token_id: "0".to_string(),
+            },
+            &[],
+        )
+        .unwrap();
+        app.execute_contract(
+            Addr::unchecked(ADMIN),
+            contract_addr.clone(),
+            &ExecuteMsg::SetWhitelist {
+                target: escrow.to_string(),
+                state: true,
+            },
+            &[],
+        )
+        .unwrap();
+
+        let owner_of: OwnerOfResponse = app
+            .wrap()
+            .query_wasm_smart(
+                contract_addr.clone(),
+                &QueryMsg::OwnerOf {
+                    token_id: "0".to_string(),
+                },
+            )
+            .unwrap();
+        assert_eq!(owner_of.owner, escrow.to_string());
+        let balance: BalanceResponse = app
+            .wrap()
+            .query_wasm_smart(
+                contract_addr.clone(),
+                &QueryMsg::Balance {
+                    address: escrow.to_string(),
+                },
+            )
+            .unwrap();
+        assert_eq!(balance.balance, Uint128::new(1_000_000));
+        let report: VerifyAccountingResponse = app
+            .wrap()
+            .query_wasm_smart(
+                contract_addr,
+                &QueryMsg::VerifyAccounting {
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap();
+        assert!(report.mismatches.is_empty());
+        assert_eq!(report.skip_held_nfts, 1);
+    }
+
+    #[test]
+    fn locked_token_is_not_burned() {
+        let (mut app, contract_addr) = proper_instantiate();
+
+        app.execute_contract(
+            Addr::unchecked(ADMIN),
+            contract_addr.clone(),
+            &ExecuteMsg::Lock {
+                token_ids: vec!["0".to_string()],
+            },
+            &[],
+        )
+        .unwrap();
+        let tokens: TokensResponse = app
+            .wrap()
+            .query_wasm_smart(
+                contract_addr.clone(),
+                &QueryMsg::LockedTokens {
+                    owner: ADMIN.to_string(),
+                },
+            )
+            .unwrap();
+        assert_eq!(tokens.tokens, vec!["0".to_string()]);
+
+        // dropping below one unit would have to burn the locked token
+        app.execute_contract(
+            Addr::unchecked(ADMIN),
+            contract_addr.clone(),
+            &ExecuteMsg::Transfer {
+                recipient: USER1.to_string(),
+                amount: 1,
+            },
+            &[],
+        )
+        .unwrap_err();
+
+        app.execute_contract(
+            Addr::unchecked(ADMIN),
+            contract_addr.clone(),
+            &ExecuteMsg::Unlock {
+                token_ids: vec!["0".to_string()],
+            },
+            &[],
+        )
+        .unwrap();
+        app.execute_contract(
+            Addr::unchecked(ADMIN),
+            contract_addr.clone(),
+            &ExecuteMsg::Transfer {
+                recipient: USER1.to_string(),
+                amount: 1,
+            },
+            &[],
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn migrate_adjusts_supply_and_units() {
+        let mut app = App::default();
+        let code_id = app.store_code(challenge_contract());
+        let contract_addr = app
+            .instantiate_contract(
+                code_id,
+                Addr::unchecked(ADMIN),
+                &InstantiateMsg {
+                    name: "Name".to_string(),
+                    symbol: "Symbol".to_string(),
+                    decimals: 6u8,
+                    total_native_supply: 1,
+                    token_uri: "token_uri".to_string(),
+                    units_per_nft: Uint128::new(1_000_000),
+                    underlying: None,
+                },
+                &[],
+                "test",
+                Some(ADMIN.to_string()),
+            )
+            .unwrap();
+        app.execute_contract(
+            Addr::unchecked(ADMIN),
+            contract_addr.clone(),
+            &ExecuteMsg::Mint { proof: None },
+            &[],
+        )
+        .unwrap();
+
+        // the whole supply is circulating, it cannot shrink
+        app.migrate_contract(
+            Addr::unchecked(ADMIN),
+            contract_addr.clone(),
+            &MigrateMsg {
+                total_supply: Some(Uint128::new(500_000)),
+                token_uri: None,
+                units_per_nft: None,
+            },
+            code_id,
+        )
+        .unwrap_err();
+
+        app.migrate_contract(
+            Addr::unchecked(ADMIN),
+            contract_addr.clone(),
+            &MigrateMsg {
+                total_supply: Some(Uint128::new(2_000_000)),
+                token_uri: None,
+                units_per_nft: Some(Uint128::new(500_000)),
+            },
+            code_id,
+        )
+        .unwrap();
+
+        let token_info: TokenInfoResponse = app
+            .wrap()
+            .query_wasm_smart(contract_addr.clone(), &QueryMsg::TokenInfo {})
+            .unwrap();
+        assert_eq!(token_info.total_supply, Uint128::new(2_000_000));
+        let balance_of: BalanceResponse = app
+            .wrap()
+            .query_wasm_smart(
+                contract_addr.clone(),
+                &QueryMsg::Balance {
+                    address: contract_addr.to_string(),
+                },
+            )
+            .unwrap();
+        assert_eq!(balance_of.balance, Uint128::new(1_000_000));
+
+        app.execute_contract(
+            Addr::unchecked(USER1),
+            contract_addr.clone(),
+            &ExecuteMsg::Resync {
+                start_after: None,
+                limit: None,
+            },
+            &[],
+        )
+        .unwrap();
+        let tokens: TokensResponse = app
+            .wrap()
+            .query_wasm_smart(
+                contract_addr.clone(),
+                &QueryMsg::Tokens {
+                    owner: ADMIN.to_string(),
+                },
+            )
+            .unwrap();
+        assert_eq!(tokens.tokens.len(), 2);
+    }
+
+    #[test]
+    fn resync_spares_escrowed_and_locked_nfts() {
+        let mut app = App::default();
+        let code_id = app.store_code(challenge_contract());
+        let contract_addr = app
+            .instantiate_contract(
+                code_id,
+                Addr::unchecked(ADMIN),
+                &InstantiateMsg {
+                    name: "Name".to_string(),
+                    symbol: "Symbol".to_string(),
+                    decimals: 6u8,
+                    total_native_supply: 4,
+                    token_uri: "token_uri".to_string(),
+                    units_per_nft: Uint128::new(1_000_000),
+                    underlying: None,
+                },
+                &[],
+                "test",
+                Some(ADMIN.to_string()),
+            )
+            .unwrap();
+        // any contract address will do as the escrow
+        let escrow = app
+            .instantiate_contract(
+                code_id,
+                Addr::unchecked(ADMIN),
+                &InstantiateMsg {
+                    name: "Escrow".to_string(),
+                    symbol: "ESC".to_string(),
+                    decimals: 6u8,
+                    total_native_supply: 1,
+                    token_uri: "token_uri".to_string(),
+                    units_per_nft: Uint128::new(1_000_000),
+                    underlying: None,
+                },
+                &[],
+                "escrow",
+                None,
+            )
+            .unwrap();
+
+        app.execute_contract(
+            Addr::unchecked(ADMIN),
+            contract_addr.clone(),
+            &ExecuteMsg::AirdropNft {
+                recipient: USER1.to_string(),
+                nft_amount: 3,
+            },
+            &[],
+        )
+        .unwrap();
+        app.execute_contract(
+            Addr::unchecked(USER1),
+            contract_addr.clone(),
+            &ExecuteMsg::Lock {
+                token_ids: vec!["0".to_string(), "1".to_string()],
+            },
+            &[],
+        )
+        .unwrap();
+        app.execute_contract(
+            Addr::unchecked(USER1),
+            contract_addr.clone(),
+            &Cw721ExecuteMsg::TransferNft {
+                recipient: escrow.to_string(),
+                token_id: "2".to_string(),
+            },
+            &[],
+        )
+        .unwrap();
+
+        // one NFT per two units: user1 would have to give up a locked NFT
+        app.migrate_contract(
+            Addr::unchecked(ADMIN),
+            contract_addr.clone(),
+            &MigrateMsg {
+                total_supply: None,
+                token_uri: None,
+                units_per_nft: Some(Uint128::new(2_000_000)),
+            },
+            code_id,
+        )
+        .unwrap();
+        app.execute_contract(
+            Addr::unchecked(ADMIN),
+            contract_addr.clone(),
+            &ExecuteMsg::Resync {
+                start_after: None,
+                limit: Some(1),
+            },
+            &[],
+        )
+        .unwrap();
+        app.execute_contract(
+            Addr::unchecked(ADMIN),
+            contract_addr.clone(),
+            &ExecuteMsg::Resync {
+                start_after: Some(ADMIN.to_string()),
+                limit: None,
+            },
+            &[],
+        )
+        .unwrap();
+
+        let owner_of: OwnerOfResponse = app
+            .wrap()
+            .query_wasm_smart(
+                contract_addr.clone(),
+                &QueryMsg::OwnerOf {
+                    token_id: "2".to_string(),
+                },
+            )
+            .unwrap();
+        assert_eq!(owner_of.owner, escrow.to_string());
+        let tokens: TokensResponse = app
+            .wrap()
+            .query_wasm_smart(
+                contract_addr,
+                &QueryMsg::Tokens {
+                    owner: USER1.to_string(),
+                },
+            )
+            .unwrap();
+        assert_eq!(tokens.tokens, vec!["0".to_string(), "1".to_string()]);
+    }
+
+    #[test]
+    fn claim_schedule_limits_mint() {
+        let mut app = App::default();
+        let code_id = app.store_code(challenge_contract());
+        let contract_addr = app
+            .instantiate_contract(
+                code_id,
+                Addr::unchecked(ADMIN),
+                &InstantiateMsg {
+                    name: "Name".to_string(),
+                    symbol: "Symbol".to_string(),
+                    decimals: 6u8,
+                    total_native_supply: 10,
+                    token_uri: "token_uri".to_string(),
+                    units_per_nft: Uint128::new(1_000_000),
+                    underlying: None,
+                },
+                &[],
+                "test",
+                None,
+            )
+            .unwrap();
+
+        let config = ClaimConfigMsg {
+            amount: Uint128::new(1_000_000),
+            cooldown: 60,
+            per_address_cap: Some(Uint128::new(2_000_000)),
+            global_cap: None,
+            start_time: None,
+            end_time: None,
+            fee: None,
+            treasury: None,
+            merkle_root: None,
+        };
+        app.execute_contract(
+            Addr::unchecked(USER1),
+            contract_addr.clone(),
+            &ExecuteMsg::UpdateClaimConfig {
+                config: config.clone(),
+            },
+            &[],
+        )
+        .unwrap_err();
+        app.execute_contract(
+            Addr::unchecked(ADMIN),
+            contract_addr.clone(),
+            &ExecuteMsg::UpdateClaimConfig { config },
+            &[],
+        )
+        .unwrap();
+
+        let mint = ExecuteMsg::Mint { proof: None };
+        app.execute_contract(Addr::unchecked(USER1), contract_addr.clone(), &mint, &[])
+            .unwrap();
+        app.execute_contract(Addr::unchecked(USER1), contract_addr.clone(), &mint, &[])
+            .unwrap_err();
+
+        app.update_block(|block| block.time = block.time.plus_seconds(60));
+        app.execute_contract(Addr::unchecked(USER1), contract_addr.clone(), &mint, &[])
+            .unwrap();
+
+        app.update_block(|block| block.time = block.time.plus_seconds(60));
+        app.execute_contract(Addr::unchecked(USER1), contract_addr.clone(), &mint, &[])
+            .unwrap_err();
+
+        let status: ClaimStatusResponse = app
+            .wrap()
+            .query_wasm_smart(
+                contract_addr.clone(),
+                &QueryMsg::ClaimStatus {
+                    address: USER1.to_string(),
+                },
+            )
+            .unwrap();
+        assert_eq!(status.claimed, Uint128::new(2_000_000));
+        assert_eq!(status.remaining, Some(Uint128::zero()));
+
+        // a zero fee could never be paid
+        let free = ClaimConfigMsg {
+            amount: Uint128::new(1_000_000),
+            cooldown: u64::MAX,
+            per_address_cap: None,
+            global_cap: None,
+            start_time: None,
+            end_time: None,
+            fee: Some(cosmwasm_std::coin(0, "uxion")),
+            treasury: Some(ADMIN.to_string()),
+            merkle_root: None,
+        };
+        app.execute_contract(
+            Addr::unchecked(ADMIN),
+            contract_addr.clone(),
+            &ExecuteMsg::UpdateClaimConfig {
+                config: free.clone(),
+            },
+            &[],
+        )
+        .unwrap_err();
+
+        // a cooldown that overflows simply never ends
+        app.execute_contract(
+            Addr::unchecked(ADMIN),
+            contract_addr.clone(),
+            &ExecuteMsg::UpdateClaimConfig {
+                config: ClaimConfigMsg {
+                    fee: None,
+                    treasury: None,
+                    ..free
+                },
+            },
+            &[],
+        )
+        .unwrap();
+        app.execute_contract(Addr::unchecked(ADMIN), contract_addr.clone(), &mint, &[])
+            .unwrap();
+        app.execute_contract(Addr::unchecked(ADMIN), contract_addr.clone(), &mint, &[])
+            .unwrap_err();
+        let _: ClaimStatusResponse = app
+            .wrap()
+            .query_wasm_smart(
+                contract_addr.clone(),
+                &QueryMsg::ClaimStatus {
+                    address: ADMIN.to_string(),
+                },
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn reveal_assigns_traits() {
+        let (mut app, contract_addr) = proper_instantiate();
+
+        app.execute_contract(
+            Addr::unchecked(ADMIN),
+            contract_addr.clone(),
+            &ExecuteMsg::UpdateNftMetadata {
+                base_uri: Some("ipfs://collection/{id}.json".to_string()),
+                placeholder_uri: Some("ipfs://hidden.json".to_string()),
+                traits: Some(vec![TraitDef {
+                    trait_type: "color".to_string(),
+                    values: vec!["red".to_string(), "blue".to_string()],
+                }]),
+            },
+            &[],
+        )
+        .unwrap();
+
+        let nft_info: NftInfoResponse<NftExtension> = app
+            .wrap()
+            .query_wasm_smart(
+                contract_addr.clone(),
+                &QueryMsg::NftInfo {
+                    token_id: "0".to_string(),
+                },
+            )
+            .unwrap();
+        assert_eq!(nft_info.token_uri, Some("ipfs://hidden.json".to_string()));
+        assert!(nft_info.extension.attributes.is_empty());
+
+        // an empty placeholder clears it, unrevealed tokens fall back to the base uri
+        app.execute_contract(
+            Addr::unchecked(ADMIN),
+            contract_addr.clone(),
+            &ExecuteMsg::UpdateNftMetadata {
+                base_uri: None,
+                placeholder_uri: Some(String::new()),
+                traits: None,
+            },
+            &[],
+        )
+        .unwrap();
+        let nft_info: NftInfoResponse<NftExtension> = app
+            .wrap()
+            .query_wasm_smart(
+                contract_addr.clone(),
+                &QueryMsg::NftInfo {
+                    token_id: "0".to_string(),
+                },
+            )
+            .unwrap();
+        assert_eq!(
+            nft_info.token_uri,
+            Some("ipfs://collection/0.json".to_string())
+        );
+
+        app.execute_contract(
+            Addr::unchecked(ADMIN),
+            contract_addr.clone(),
+            &ExecuteMsg::Reveal {},
+            &[],
+        )
+        .unwrap();
+
+        let nft_info: NftInfoResponse<NftExtension> = app
+            .wrap()
+            .query_wasm_smart(
+                contract_addr.clone(),
+                &QueryMsg::NftInfo {
+                    token_id: "0".to_string(),
+                },
+            )
+            .unwrap();
+        assert_eq!(
+            nft_info.token_uri,
+            Some("ipfs://collection/0.json".to_string())
+        );
+        assert_eq!(nft_info.extension.attributes.len(), 1);
+        assert!(["red", "blue"].contains(&nft_info.extension.attributes[0].value.as_str()));
+
+        // the seed is frozen once revealed
+        app.execute_contract(
+            Addr::unchecked(ADMIN),
+            contract_addr.clone(),
+            &ExecuteMsg::Reveal {},
+            &[],
+        )
+        .unwrap_err();
+    }
+
+    #[test]
+    fn mirror_relays_nft_transfer() {
+        let (mut app, contract_addr) = proper_instantiate();
+        let mirror = "mirror";
+
+        app.execute_contract(
+            Addr::unchecked(ADMIN),
+            contract_addr.clone(),
+            &ExecuteMsg::SetMirror {
+                mirror: mirror.to_string(),
+            },
+            &[],
+        )
+        .unwrap();
+
+        let relay = ExecuteMsg::Mirror {
+            sender: ADMIN.to_string(),
+            msg: MirrorMsg::TransferNft {
+                recipient: USER1.to_string(),
+                token_id: "0".to_string(),
+            },
+        };
+        // only the registered mirror may speak for other accounts
+        app.execute_contract(Addr::unchecked(USER1), contract_addr.clone(), &relay, &[])
+            .unwrap_err();
+        app.execute_contract(Addr::unchecked(mirror), contract_addr.clone(), &relay, &[])
+            .unwrap();
+
+        let owner_of: OwnerOfResponse = app
+            .wrap()
+            .query_wasm_smart(
+                contract_addr.clone(),
+                &QueryMsg::OwnerOf {
+                    token_id: "0".to_string(),
+                },
+            )
+            .unwrap();
+        assert_eq!(owner_of.owner, USER1.to_string());
+    }
+
+    #[test]
+    fn ownership_transfer_and_roles() {
+        let (mut app, contract_addr) = proper_instantiate();
+        let manager = "manager";
+
+        app.execute_contract(
+            Addr::unchecked(ADMIN),
+            contract_addr.clone(),
+            &ExecuteMsg::SetRole {
+                role: Role::WhitelistManager,
+                address: Some(manager.to_string()),
+            },
+            &[],
+        )
+        .unwrap();
+        let set_whitelist = ExecuteMsg::SetWhitelist {
+            target: USER1.to_string(),
+            state: true,
+        };
+        app.execute_contract(
+            Addr::unchecked(manager),
+            contract_addr.clone(),
+            &set_whitelist,
+            &[],
+        )
+        .unwrap();
+        // a role does not reach beyond its own duty
+        app.execute_contract(
+            Addr::unchecked(manager),
+            contract_addr.clone(),
+            &ExecuteMsg::AirdropNft {
+                recipient: manager.to_string(),
+                nft_amount: 1,
+            },
+            &[],
+        )
+        .unwrap_err();
+
+        app.execute_contract(
+            Addr::unchecked(ADMIN),
+            contract_addr.clone(),
+            &ExecuteMsg::UpdateOwnership(Action::TransferOwnership {
+                new_owner: USER1.to_string(),
+                expiry: None,
+            }),
+            &[],
+        )
+        .unwrap();
+        app.execute_contract(
+            Addr::unchecked(USER1),
+            contract_addr.clone(),
+            &ExecuteMsg::UpdateOwnership(Action::AcceptOwnership),
+            &[],
+        )
+        .unwrap();
+
+        let ownership: Ownership<Addr> = app
+            .wrap()
+            .query_wasm_smart(contract_addr.clone(), &QueryMsg::Ownership {})
+            .unwrap();
+        assert_eq!(ownership.owner, Some(Addr::unchecked(USER1)));
+        app.execute_contract(
+            Addr::unchecked(ADMIN),
+            contract_addr.clone(),
+            &set_whitelist,
+            &[],
+        )
+        .unwrap_err();
+
+        let roles: Roles = app
+            .wrap()
+            .query_wasm_smart(contract_addr.clone(), &QueryMsg::Roles {})
+            .unwrap();
+        assert_eq!(roles.whitelist_manager, Some(Addr::unchecked(manager)));
+
+        let config: ConfigResponse = app
+            .wrap()
+            .query_wasm_smart(contract_addr.clone(), &QueryMsg::Config {})
+            .unwrap();
+        assert_eq!(config.admin, USER1);
+        app.execute_contract(
+            Addr::unchecked(USER1),
+            contract_addr.clone(),
+            &ExecuteMsg::UpdateOwnership(Action::RenounceOwnership),
+            &[],
+        )
+        .unwrap();
+        let config: ConfigResponse = app
+            .wrap()
+            .query_wasm_smart(contract_addr, &QueryMsg::Config {})
+            .unwrap();
+        assert_eq!(config.admin, "");
+    }
+
+    #[test]
+    fn transfers_emit_structured_events() {
+        let (mut app, contract_addr) = proper_instantiate();
+
+        let res = app
+            .execute_contract(
+                Addr::unchecked(ADMIN),
+                contract_addr.clone(),
+                &ExecuteMsg::Transfer {
+                    recipient: USER1.to_string(),
+                    amount: 1_000_000,
+                },
+                &[],
+            )
+            .unwrap();
+
+        let transfer = res.events.iter().find(|e| e.ty == "wasm-transfer").unwrap();
+        assert!(transfer
+            .attributes
+            .iter()
+            .any(|a| a.key == "amount" && a.value == "1000000"));
+        let burn = res.events.iter().find(|e| e.ty == "wasm-nft_burn").unwrap();
+        assert!(burn
+            .attributes
+            .iter()
+            .any(|a| a.key == "token_ids" && a.value == "0"));
+        let mint = res.events.iter().find(|e| e.ty == "wasm-nft_mint").unwrap();
+        assert!(mint
+            .attributes
+            .iter()
+            .any(|a| a.key == "token_ids" && a.value == "1"));
+
+        let res = app
+            .execute_contract(
+                Addr::unchecked(USER1),
+                contract_addr.clone(),
+                &ExecuteMsg::TransferNft {
+                    recipient: ADMIN.to_string(),
+                    token_id: "1".to_string(),
+                },
+                &[],
+            )
+            .unwrap();
+        let nft_transfer = res
+            .events
+            .iter()
+            .find(|e| e.ty == "wasm-nft_transfer")
+            .unwrap();
+        assert!(nft_transfer
+            .attributes
+            .iter()
+            .any(|a| a.key == "to" && a.value == ADMIN));
+        assert!(res.events.iter().any(|e| e.ty == "wasm-transfer"));
+    }
+
+    #[test]
+    fn batch_airdrop_and_transfer() {
+        let mut app = App::default();
+        let challenge_id = app.store_code(challenge_contract());
+        let contract_addr = app
+            .instantiate_contract(
+                challenge_id,
+                Addr::unchecked(ADMIN),
+                &InstantiateMsg {
+                    name: "Name".to_string(),
+                    symbol: "Symbol".to_string(),
+                    decimals: 6u8,
+                    total_native_supply: 5,
+                    token_uri: "token_uri".to_string(),
+                    units_per_nft: Uint128::new(1_000_000),
+                    underlying: None,
+                },
+                &[],
+                "test",
+                None,
+            )
+            .unwrap();
+
+        let too_many = (0..51).map(|_| (USER1.to_string(), 1)).collect();
+        app.execute_contract(
+            Addr::unchecked(ADMIN),
+            contract_addr.clone(),
+            &ExecuteMsg::BatchAirdrop {
+                recipients: too_many,
+            },
+            &[],
+        )
+        .unwrap_err();
+        app.execute_contract(
+            Addr::unchecked(ADMIN),
+            contract_addr.clone(),
+            &ExecuteMsg::BatchAirdrop {
+                recipients: vec![(USER1.to_string(), 3), ("user2".to_string(), 1)],
+            },
+            &[],
+        )
+        .unwrap();
+
+        let tokens: TokensResponse = app
+            .wrap()
+            .query_wasm_smart(
+                contract_addr.clone(),
+                &QueryMsg::Tokens {
+                    owner: USER1.to_string(),
+                },
+            )
+            .unwrap();
+        assert_eq!(tokens.tokens, vec!["0", "1", "2"]);
+
+        app.execute_contract(
+            Addr::unchecked(USER1),
+            contract_addr.clone(),
+            &ExecuteMsg::BatchTransferNft {
+                recipient: "user2".to_string(),
+                token_ids: vec!["0".to_string(), "2".to_string()],
+            },
+            &[],
+        )
+        .unwrap();
+
+        let tokens: TokensResponse = app
+            .wrap()
+            .query_wasm_smart(
+                contract_addr.clone(),
+                &QueryMsg::Tokens {
+                    owner: "user2".to_string(),
+                },
+            )
+            .unwrap();
+        assert_eq!(tokens.tokens, vec!["3", "0", "2"]);
+
+        // the remaining id kept a valid index and still moves
+        app.execute_contract(
+            Addr::unchecked(USER1),
+            contract_addr.clone(),
+            &ExecuteMsg::TransferNft {
+                recipient: "user2".to_string(),
+                token_id: "1".to_string(),
+            },
+            &[],
+        )
+        .unwrap();
+        let balance: BalanceResponse = app
+            .wrap()
+            .query_wasm_smart(
+                contract_addr.clone(),
+                &QueryMsg::Balance {
+                    address: "user2".to_string(),
+                },
+            )
+            .unwrap();
+        assert_eq!(balance.balance, Uint128::new(4_000_000));
+    }
+
+    #[cosmwasm_schema::cw_serde]
+    enum SinkMsg {
+        Receive(cw20::Cw20ReceiveMsg),
+    }
+
+    /// A contract that accepts any cw20 `Send` and does nothing with it
+    fn sink_contract() -> Box<dyn Contract<Empty>> {
+        Box::new(ContractWrapper::new(
+            |_: DepsMut, _: Env, _: MessageInfo, _: SinkMsg| -> StdResult<Response> {
+                Ok(Response::new())
+            },
+            |_: DepsMut, _: Env, _: MessageInfo, _: Empty| -> StdResult<Response> {
+                Ok(Response::new())
+            },
+            |_: Deps, _: Env, _: Empty| -> StdResult<Binary> { to_json_binary(&Empty {}) },
+        ))
+    }
+
+    const HOLDERS: [&str; 4] = ["user0", "user1", "user2", "user3"];
+
+    #[derive(Debug, Clone)]
+    enum Op {
+        Transfer {
+            from: usize,
+            to: usize,
+            amount: u128,
+        },
+        Send {
+            from: usize,
+            amount: u128,
+        },
+        Whitelist {
+            who: usize,
+            state: bool,
+        },
+        TransferNft {
+            from: usize,
+            to: usize,
+            pick: usize,
+        },
+    }
+
+    fn op_strategy() -> impl Strategy<Value = Op> {
+        prop_oneof![
+            (0..4usize, 0..4usize, 0..3_000_000u128).prop_map(|(from, to, amount)| Op::Transfer {
+                from,
+                to,
+                amount
+            }),
+            (0..4usize, 0..3_000_000u128).prop_map(|(from, amount)| Op::Send { from, amount }),
+            (0..4usize, any::<bool>()).prop_map(|(who, state)| Op::Whitelist { who, state }),
+            (0..4usize, 0..4usize, 0..8usize).prop_map(|(from, to, pick)| Op::TransferNft {
+                from,
+                to,
+                pick
+            }),
+        ]
+    }
+
+    fn accounting_setup() -> (App, Addr, Addr) {
+        let mut app = App::default();
+        let challenge_id = app.store_code(challenge_contract());
+        let sink_id = app.store_code(sink_contract());
+
+        let contract_addr = app
+            .instantiate_contract(
+                challenge_id,
+                Addr::unchecked(ADMIN),
+                &InstantiateMsg {
+                    name: "Name".to_string(),
+                    symbol: "Symbol".to_string(),
+                    decimals: 6u8,
+                    total_native_supply: 20,
+                    token_uri: "token_uri".to_string(),
+                    units_per_nft: Uint128::new(1_000_000),
+                    underlying: None,
+                },
+                &[],
+                "test",
+                None,
+            )
+            .unwrap();
+        let sink = app
+            .instantiate_contract(
+                sink_id,
+                Addr::unchecked(ADMIN),
+                &Empty {},
+                &[],
+                "sink",
+                None,
+            )
+            .unwrap();
+
+        app.execute_contract(
+            Addr::unchecked(ADMIN),
+            contract_addr.clone(),
+            &ExecuteMsg::BatchAirdrop {
+                recipients: HOLDERS.iter().map(|h| (h.to_string(), 4)).collect(),
+            },
+            &[],
+        )
+        .unwrap();
+
+        (app, contract_addr, sink)
+    }
+
+    fn apply(app: &mut App, contract_addr: &Addr, sink: &Addr, op: Op) {
+        // rejected operations roll back, only the accounting afterwards matters
+        let _ = match op {
+            Op::Transfer { from, to, amount } => app.execute_contract(
+                Addr::unchecked(HOLDERS[from]),
+                contract_addr.clone(),
+                &ExecuteMsg::Transfer {
+                    recipient: HOLDERS[to].to_string(),
+                    amount,
+                },
+                &[],
+            ),
+            Op::Send { from, amount } => app.execute_contract(
+                Addr::unchecked(HOLDERS[from]),
+                contract_addr.clone(),
+                &ExecuteMsg::Send {
+                    contract: sink.to_string(),
+                    amount,
+                    msg: Binary::default(),
+                },
+                &[],
+            ),
+            Op::Whitelist { who, state } => {
+                let res = app.execute_contract(
+                    Addr::unchecked(ADMIN),
+                    contract_addr.clone(),
+                    &ExecuteMsg::SetWhitelist {
+                        target: HOLDERS[who].to_string(),
+                        state,
+                    },
+                    &[],
+                );
+                if res.is_ok() {
+                    // toggling the whitelist reconciles the holder right away
+                    let report: VerifyAccountingResponse = app
+                        .wrap()
+                        .query_wasm_smart(
+                            contract_addr.clone(),
+                            &QueryMsg::VerifyAccounting {
+                                start_after: None,
+                                limit: Some(100),
+                            },
+                        )
+                        .unwrap();
+                    assert!(report.mismatches.is_empty(), "{:?}", report.mismatches);
+                }
+                res
+            }
+            Op::TransferNft { from, to, pick } => {
+                let owned: TokensResponse = app
+                    .wrap()
+                    .query_wasm_smart(
+                        contract_addr.clone(),
+                        &QueryMsg::Tokens {
+                            owner: HOLDERS[from].to_string(),
+                        },
+                    )
+                    .unwrap();
+                if owned.tokens.is_empty() {
+                    return;
+                }
+                app.execute_contract(
+                    Addr::unchecked(HOLDERS[from]),
+                    contract_addr.clone(),
+                    &ExecuteMsg::TransferNft {
+                        recipient: HOLDERS[to].to_string(),
+                        token_id: owned.tokens[pick % owned.tokens.len()].clone(),
+                    },
+                    &[],
+                )
+            }
+        };
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(32))]
+
+        #[test]
+        fn accounting_holds_under_random_ops(ops in prop::collection::vec(op_strategy(), 1..40)) {
+            let (mut app, contract_addr, sink) = accounting_setup();
+
+            for op in ops {
+                apply(&mut app, &contract_addr, &sink, op);
+            }
+
+            let report: VerifyAccountingResponse = app
+                .wrap()
+                .query_wasm_smart(
+                    contract_addr.clone(),
+                    &QueryMsg::VerifyAccounting {
+                        start_after: None,
+                        limit: Some(100),
+                    },
+                )
+                .unwrap();
+            prop_assert!(report.next.is_none());
+            prop_assert!(report.mismatches.is_empty(), "{:?}", report.mismatches);
+            prop_assert_eq!(report.page_balance, report.total_supply);
+
+            let report: VerifyTokensResponse = app
+                .wrap()
+                .query_wasm_smart(
+                    contract_addr.clone(),
+                    &QueryMsg::VerifyTokens {
+                        start_after: None,
+                        limit: Some(100),
+                    },
+                )
+                .unwrap();
+            prop_assert!(report.next.is_none());
+            prop_assert!(report.mismatches.is_empty(), "{:?}", report.mismatches);
+            prop_assert_eq!(Uint128::from(report.checked), report.live_nfts);
+        }
+    }
+
+    #[test]
+    fn holder_enumeration_and_supply_stats() {
+        let (mut app, contract_addr) = proper_instantiate();
+
+        let stats: SupplyStatsResponse = app
+            .wrap()
+            .query_wasm_smart(contract_addr.clone(), &QueryMsg::SupplyStats {})
+            .unwrap();
+        assert_eq!(
+            stats,
+            SupplyStatsResponse {
+                total_supply: Uint128::new(1_000_000),
+                contract_balance: Uint128::zero(),
+                circulating: Uint128::new(1_000_000),
+                live_nfts: Uint128::one(),
+            }
+        );
+
+        app.execute_contract(
+            Addr::unchecked(ADMIN),
+            contract_addr.clone(),
+            &ExecuteMsg::Transfer {
+                recipient: USER1.to_string(),
+                amount: 1_000_000,
+            },
+            &[],
+        )
+        .unwrap();
+
+        // the admin emptied its balance, so the count is unchanged
+        let holders: u64 = app
+            .wrap()
+            .query_wasm_smart(contract_addr.clone(), &QueryMsg::HolderCount {})
+            .unwrap();
+        assert_eq!(holders, 1);
+
+        let accounts: AllAccountsResponse = app
+            .wrap()
+            .query_wasm_smart(
+                contract_addr.clone(),
+                &QueryMsg::AllAccounts {
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap();
+        // neither the emptied admin nor the empty contract is listed
+        assert_eq!(accounts.accounts, vec![USER1.to_string()]);
+
+        // the admin's NFT was burned and a new one minted to user1
+        let n_tokens: NumTokensResponse = app
+            .wrap()
+            .query_wasm_smart(contract_addr.clone(), &Cw721QueryMsg::NumTokens {})
+            .unwrap();
+        assert_eq!(n_tokens.count, 1);
+
+        let whitelisted: WhitelistedResponse = app
+            .wrap()
+            .query_wasm_smart(
+                contract_addr.clone(),
+                &QueryMsg::Whitelisted {
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap();
+        assert_eq!(whitelisted.addresses, vec![contract_addr.to_string()]);
+    }
+
+    #[test]
+    fn wrap_and_unwrap_native() {
+        let mut app = App::new(|router, _, storage| {
+            router
+                .bank
+                .init_balance(storage, &Addr::unchecked(USER1), coins(5_000_000, "uatom"))
+                .unwrap();
+        });
+        let challenge_id = app.store_code(challenge_contract());
+        let contract_addr = app
+            .instantiate_contract(
+                challenge_id,
+                Addr::unchecked(ADMIN),
+                &InstantiateMsg {
+                    name: "Wrapped".to_string(),
+                    symbol: "WATOM".to_string(),
+                    decimals: 6u8,
+                    total_native_supply: 0,
+                    token_uri: "token_uri".to_string(),
+                    units_per_nft: Uint128::new(1_000_000),
+                    underlying: Some(Denom::Native("uatom".to_string())),
+                },
+                &[],
+                "test",
+                None,
+            )
+            .unwrap();
+
+        app.execute_contract(
+            Addr::unchecked(USER1),
+            contract_addr.clone(),
+            &ExecuteMsg::Wrap {},
+            &coins(2_500_000, "uatom"),
+        )
+        .unwrap();
+
+        let tokens: TokensResponse = app
+            .wrap()
+            .query_wasm_smart(
+                contract_addr.clone(),
+                &QueryMsg::Tokens {
+                    owner: USER1.to_string(),
+                },
+            )
+            .unwrap();
+        assert_eq!(tokens.tokens.len(), 2);
+
+        app.execute_contract(
+            Addr::unchecked(USER1),
+            contract_addr.clone(),
+            &ExecuteMsg::Unwrap { amount: 1_500_000 },
+            &[],
+        )
+        .unwrap();
+
+        let reserve: ReserveResponse = app
+            .wrap()
+            .query_wasm_smart(contract_addr.clone(), &QueryMsg::Reserve {})
+            .unwrap();
+        assert_eq!(reserve.reserve, Uint128::new(1_000_000));
+        assert_eq!(reserve.wrapped, Uint128::new(1_000_000));
+        assert!(reserve.fully_backed);
+
+        let tokens: TokensResponse = app
+            .wrap()
+            .query_wasm_smart(
+                contract_addr.clone(),
+                &QueryMsg::Tokens {
+                    owner: USER1.to_string(),
+                },
+            )
+            .unwrap();
+        assert_eq!(tokens.tokens.len(), 1);
+        let native = app.wrap().query_balance(USER1, "uatom").unwrap();
+        assert_eq!(native.amount, Uint128::new(4_000_000));
+    }
+
+    #[test]
+    fn marketing_and_collection_info() {
+        let (mut app, contract_addr) = proper_instantiate();
+
+        let png = Binary::from(b"\x89PNG\r\n\x1a\nrest-of-image".to_vec());
+        app.execute_contract(
+            Addr::unchecked(USER1),
+            contract_addr.clone(),
+            &ExecuteMsg::UploadLogo(Logo::Embedded(EmbeddedLogo::Png(png.clone()))),
+            &[],
+        )
+        .unwrap_err();
+        app.execute_contract(
+            Addr::unchecked(ADMIN),
+            contract_addr.clone(),
+            &ExecuteMsg::UploadLogo(Logo::Embedded(EmbeddedLogo::Svg(Binary::from(
+                b"<svg></svg>".to_vec(),
+            )))),
+            &[],
+        )
+        .unwrap_err();
+        app.execute_contract(
+            Addr::unchecked(ADMIN),
+            contract_addr.clone(),
+            &ExecuteMsg::UploadLogo(Logo::Embedded(EmbeddedLogo::Png(png.clone()))),
+            &[],
+        )
+        .unwrap();
+
+        let logo: DownloadLogoResponse = app
+            .wrap()
+            .query_wasm_smart(contract_addr.clone(), &QueryMsg::DownloadLogo {})
+            .unwrap();
+        assert_eq!(logo.mime_type, "image/png");
+        assert_eq!(logo.data, png);
+
+        // the marketing address may then maintain the project details itself
+        app.execute_contract(
+            Addr::unchecked(ADMIN),
+            contract_addr.clone(),
+            &ExecuteMsg::UpdateMarketing {
+                project: Some("https://example.com".to_string()),
+                description: None,
+                marketing: Some(USER1.to_string()),
+            },
+            &[],
+        )
+        .unwrap();
+        app.execute_contract(
+            Addr::unchecked(USER1),
+            contract_addr.clone(),
+            &ExecuteMsg::UpdateMarketing {
+                project: Some("".to_string()),
+                description: Some("Duality token".to_string()),
+                marketing: None,
+            },
+            &[],
+        )
+        .unwrap();
+
+        let marketing: MarketingInfoResponse = app
+            .wrap()
+            .query_wasm_smart(contract_addr.clone(), &QueryMsg::MarketingInfo {})
+            .unwrap();
+        assert_eq!(
+            marketing,
+            MarketingInfoResponse {
+                project: None,
+                description: Some("Duality token".to_string()),
+                logo: Some(LogoInfo::Embedded),
+                marketing: Some(Addr::unchecked(USER1)),
+            }
+        );
+
+        // collection info stays with the owner
+        let update = ExecuteMsg::UpdateCollectionInfo {
+            name: Some("Renamed".to_string()),
+            symbol: None,
+            token_uri: None,
+        };
+        app.execute_contract(Addr::unchecked(USER1), contract_addr.clone(), &update, &[])
+            .unwrap_err();
+        app.execute_contract(Addr::unchecked(ADMIN), contract_addr.clone(), &update, &[])
+            .unwrap();
+
+        let info: ContractInfoResponse = app
+            .wrap()
+            .query_wasm_smart(contract_addr.clone(), &QueryMsg::ContractInfo {})
+            .unwrap();
+        assert_eq!(info.name, "Renamed");
+        assert_eq!(info.symbol, "Symbol");
+    }
+
+    #[test]
+    fn transfer_tax_routes_to_treasury() {
+        let (mut app, contract_addr) = proper_instantiate();
+
+        let update = ExecuteMsg::UpdateTaxConfig {
+            bps: Some(100),
+            treasury: Some("treasury".to_string()),
+            add_exempt: None,
+            remove_exempt: None,
+        };
+        app.execute_contract(Addr::unchecked(USER1), contract_addr.clone(), &update, &[])
+            .unwrap_err();
+        app.execute_contract(Addr::unchecked(ADMIN), contract_addr.clone(), &update, &[])
+            .unwrap();
+
+        app.execute_contract(
+            Addr::unchecked(ADMIN),
+            contract_addr.clone(),
+            &ExecuteMsg::Transfer {
+                recipient: USER1.to_string(),
+                amount: 1_000_000,
+            },
+            &[],
+        )
+        .unwrap();
+
+        for (address, expected) in [(USER1, 990_000u128), ("treasury", 10_000)] {
+            let balance: BalanceResponse = app
+                .wrap()
+                .query_wasm_smart(
+                    contract_addr.clone(),
+                    &QueryMsg::Balance {
+                        address: address.to_string(),
+                    },
+                )
+                .unwrap();
+            assert_eq!(balance.balance, Uint128::new(expected));
+        }
+
+        // the taxed amount no longer covers a whole NFT
+        let tokens: TokensResponse = app
+            .wrap()
+            .query_wasm_smart(
+                contract_addr.clone(),
+                &QueryMsg::Tokens {
+                    owner: USER1.to_string(),
+                },
+            )
+            .unwrap();
+        assert!(tokens.tokens.is_empty());
+
+        let report: VerifyAccountingResponse = app
+            .wrap()
+            .query_wasm_smart(
+                contract_addr.clone(),
+                &QueryMsg::VerifyAccounting {
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap();
+        assert!(report.mismatches.is_empty());
+
+        app.execute_contract(
+            Addr::unchecked(ADMIN),
+            contract_addr.clone(),
+            &ExecuteMsg::UpdateTaxConfig {
+                bps: None,
+                treasury: None,
+                add_exempt: Some(vec![USER1.to_string()]),
+                remove_exempt: None,
+            },
+            &[],
+        )
+        .unwrap();
+        app.execute_contract(
+            Addr::unchecked(USER1),
+            contract_addr.clone(),
+            &ExecuteMsg::Transfer {
+                recipient: ADMIN.to_string(),
+                amount: 990_000,
+            },
+            &[],
+        )
+        .unwrap();
+
+        let tax: TaxConfig = app
+            .wrap()
+            .query_wasm_smart(contract_addr.clone(), &QueryMsg::TaxConfig {})
+            .unwrap();
+        assert_eq!(tax.exempt, vec![Addr::unchecked(USER1)]);
+        let balance: BalanceResponse = app
+            .wrap()
+            .query_wasm_smart(
+                contract_addr.clone(),
+                &QueryMsg::Balance {
+                    address: ADMIN.to_string(),
+                },
+            )
+            .unwrap();
+        assert_eq!(balance.balance, Uint128::new(990_000));
+    }
 }
@@ -1,8 +1,18 @@
 use cosmwasm_schema::{cw_serde, QueryResponses};
-use cosmwasm_std::{Binary, Empty};
+use cosmwasm_std::{
+    to_json_binary, Addr, Binary, Coin, CosmosMsg, StdResult, Timestamp, Uint128, WasmMsg,
+};
 
-use cw20::{BalanceResponse, Cw20ExecuteMsg, MinterResponse, TokenInfoResponse};
-use cw721::{Cw721ExecuteMsg, NftInfoResponse, NumTokensResponse, OwnerOfResponse, TokensResponse};
+use crate::state::{Role, Roles, TraitDef};
+use cw20::{
+    AllAccountsResponse, BalanceResponse, Cw20ExecuteMsg, Cw20ReceiveMsg, Denom,
+    DownloadLogoResponse, Logo, MarketingInfoResponse, MinterResponse, TokenInfoResponse,
+};
+use cw721::{
+    ContractInfoResponse, Cw721ExecuteMsg, NftInfoResponse, NumTokensResponse, OwnerOfResponse,
+    TokensResponse,
+};
+use cw_ownable::{Action, Ownership};
 
 #[cw_serde]
 pub struct InitialBalance {
@@ -17,14 +27,42 @@ pub struct InstantiateMsg {
     pub decimals: u8,
     pub total_native_supply: u128,
     pub token_uri: String,
+    /// Fungible base units that make up one NFT
+    pub units_per_nft: Uint128,
+    /// Wrapper mode: supply is only created by depositing this asset 1:1, so
+    /// `total_native_supply` must be zero
+    pub underlying: Option<Denom>,
 }
 
 #[cw_serde]
 pub enum ExecuteMsg {
+    /// Propose, accept or renounce contract ownership
+    UpdateOwnership(Action),
+    /// Owner only. `None` clears the role.
+    SetRole {
+        role: Role,
+        address: Option<String>,
+    },
     SetWhitelist {
         target: String,
         state: bool,
     },
+    SetSkipNft {
+        skip: bool,
+    },
+    /// Anyone. Brings a page of holders' NFTs in line with their balances
+    /// after `units_per_nft` changed. Holders that skip NFTs and locked ids
+    /// are left as they are.
+    Resync {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    Lock {
+        token_ids: Vec<String>,
+    },
+    Unlock {
+        token_ids: Vec<String>,
+    },
     Approve {
         spender: String,
         amount_or_id: u128,
@@ -61,11 +99,125 @@ pub enum ExecuteMsg {
         msg: Binary,
     },
     // Wrapper code
-    Mint {},
+    /// Claims from the contract's own balance under the admin's claim policy
+    Mint {
+        /// Merkle proof of the sender, required when the policy has an allowlist
+        proof: Option<Vec<String>>,
+    },
+    UpdateClaimConfig {
+        config: ClaimConfigMsg,
+    },
+    /// Only fields that are set get updated, an empty `placeholder_uri`
+    /// clears it. Traits are frozen after `Reveal`.
+    UpdateNftMetadata {
+        base_uri: Option<String>,
+        placeholder_uri: Option<String>,
+        traits: Option<Vec<TraitDef>>,
+    },
+    /// Freezes the trait seed and switches token URIs from the placeholder
+    Reveal {},
+    /// Registers the cw721 mirror contract
+    SetMirror {
+        mirror: String,
+    },
+    /// Only accepted from the registered mirror, which passes its own caller
+    /// as `sender`
+    Mirror {
+        sender: String,
+        msg: MirrorMsg,
+    },
     AirdropNft {
         recipient: String,
         nft_amount: u128,
     },
+    /// Moves every id in `token_ids` to `recipient` in one transaction
+    BatchTransferNft {
+        recipient: String,
+        token_ids: Vec<String>,
+    },
+    /// Like `BatchTransferNft`, then calls `contract` once with a
+    /// `BatchReceiveNftMsg` listing all ids
+    BatchSendNft {
+        contract: String,
+        token_ids: Vec<String>,
+        msg: Binary,
+    },
+    /// Airdrops `nft_amount` NFTs to each recipient
+    BatchAirdrop {
+        recipients: Vec<(String, u128)>,
+    },
+    /// Wrapper mode: deposits of the underlying cw20 are wrapped for the
+    /// sender of the tokens
+    Receive(Cw20ReceiveMsg),
+    /// Wrapper mode: wraps the attached underlying native coins
+    Wrap {},
+    /// Wrapper mode: burns `amount` and returns as much of the underlying
+    Unwrap {
+        amount: u128,
+    },
+    /// Owner or marketing address. As in cw20, an empty string clears a field.
+    UpdateMarketing {
+        project: Option<String>,
+        description: Option<String>,
+        marketing: Option<String>,
+    },
+    /// Owner or marketing address
+    UploadLogo(Logo),
+    /// Owner only. A non-zero tax requires a treasury.
+    UpdateTaxConfig {
+        bps: Option<u16>,
+        treasury: Option<String>,
+        add_exempt: Option<Vec<String>>,
+        remove_exempt: Option<Vec<String>>,
+    },
+    /// Owner only. Name and symbol are shared by the cw20 and cw721 faces.
+    UpdateCollectionInfo {
+        name: Option<String>,
+        symbol: Option<String>,
+        token_uri: Option<String>,
+    },
+}
+
+/// Hook sent to the receiving contract of a `BatchSendNft`
+#[cw_serde]
+pub struct BatchReceiveNftMsg {
+    pub sender: String,
+    pub token_ids: Vec<String>,
+    pub msg: Binary,
+}
+
+impl BatchReceiveNftMsg {
+    /// serializes the message
+    pub fn into_json_binary(self) -> StdResult<Binary> {
+        let msg = ReceiverExecuteMsg::BatchReceiveNft(self);
+        to_json_binary(&msg)
+    }
+
+    /// creates a cosmos_msg sending this struct to the named contract
+    pub fn into_cosmos_msg<T: Into<String>>(self, contract_addr: T) -> StdResult<CosmosMsg> {
+        let msg = self.into_json_binary()?;
+        let execute = WasmMsg::Execute {
+            contract_addr: contract_addr.into(),
+            msg,
+            funds: vec![],
+        };
+        Ok(execute.into())
+    }
+}
+
+// This is just a helper to properly serialize the above message
+#[cw_serde]
+enum ReceiverExecuteMsg {
+    BatchReceiveNft(BatchReceiveNftMsg),
+}
+
+#[cw_serde]
+pub enum MirrorMsg {
+    TransferNft { recipient: String, token_id: String },
+    Approve { spender: String, token_id: String },
+    Revoke { spender: String, token_id: String },
+    ApproveAll { operator: String },
+    RevokeAll { operator: String },
 }
 
 impl From<Cw20ExecuteMsg> for ExecuteMsg {
@@ -94,6 +246,16 @@ impl From<Cw20ExecuteMsg> for ExecuteMsg {
                 amount: amount.u128(),
                 msg,
             },
+            Cw20ExecuteMsg::UpdateMarketing {
+                project,
+                description,
+                marketing,
+            } => ExecuteMsg::UpdateMarketing {
+                project,
+                description,
+                marketing,
+            },
+            Cw20ExecuteMsg::UploadLogo(logo) => ExecuteMsg::UploadLogo(logo),
             _ => panic!("Unsupported message"),
         }
     }
@@ -123,16 +285,151 @@ impl From<Cw721ExecuteMsg> for ExecuteMsg {
     }
 }
 
+#[cw_serde]
+pub struct ClaimConfigMsg {
+    pub amount: Uint128,
+    pub cooldown: u64,
+    pub per_address_cap: Option<Uint128>,
+    pub global_cap: Option<Uint128>,
+    pub start_time: Option<Timestamp>,
+    pub end_time: Option<Timestamp>,
+    pub fee: Option<Coin>,
+    /// Required when `fee` is set
+    pub treasury: Option<String>,
+    pub merkle_root: Option<String>,
+}
+
+#[cw_serde]
+pub struct ClaimStatusResponse {
+    pub next_claim_time: Timestamp,
+    pub claimed: Uint128,
+    /// `None` when the address has no lifetime cap
+    pub remaining: Option<Uint128>,
+    /// `None` when there is no global cap
+    pub global_remaining: Option<Uint128>,
+    pub ended: bool,
+}
+
+#[cw_serde]
+pub struct Trait {
+    pub trait_type: String,
+    pub value: String,
+}
+
+#[cw_serde]
+pub struct NftExtension {
+    pub attributes: Vec<Trait>,
+}
+
 #[cw_serde]
 pub struct ConfigResponse {
+    /// Current owner, empty once ownership is renounced
     pub admin: String,
 }
 
+#[cw_serde]
+pub struct DualityInfoResponse {
+    pub units_per_nft: Uint128,
+    pub decimals: u8,
+    pub total_supply: Uint128,
+    /// NFTs that exist when the whole supply sits with NFT holders
+    pub max_nft_supply: Uint128,
+}
+
+#[cw_serde]
+pub struct ReserveResponse {
+    pub underlying: Denom,
+    /// Underlying held by the contract
+    pub reserve: Uint128,
+    /// Wrapped supply, equal to the total supply
+    pub wrapped: Uint128,
+    pub fully_backed: bool,
+}
+
+#[cw_serde]
+pub struct WhitelistedResponse {
+    pub addresses: Vec<String>,
+}
+
+#[cw_serde]
+pub struct SupplyStatsResponse {
+    pub total_supply: Uint128,
+    /// Still held by the contract and available to `Mint`
+    pub contract_balance: Uint128,
+    pub circulating: Uint128,
+    pub live_nfts: Uint128,
+}
+
+#[cw_serde]
+pub enum AccountingMismatch {
+    /// A holder owns a different number of NFTs than its balance covers.
+    /// Holders that skip NFTs may own fewer, never more.
+    NftCount {
+        holder: String,
+        balance: Uint128,
+        expected: u128,
+        owned: u128,
+    },
+    /// An id listed under `holder` is recorded as owned by someone else
+    OwnerOf {
+        holder: String,
+        token_id: String,
+        owner: Option<String>,
+    },
+    /// An id's stored position does not match its place in the owner's list
+    OwnedIndex {
+        holder: String,
+        token_id: String,
+        index: Option<u128>,
+        expected: u128,
+    },
+    /// Only checked when a single page covers every holder
+    TotalSupply {
+        total_balance: Uint128,
+        total_supply: Uint128,
+    },
+    /// A live id is missing from its owner's list at its stored position
+    Owned {
+        token_id: String,
+        owner: String,
+        index: Option<u128>,
+    },
+}
+
+#[cw_serde]
+pub struct VerifyAccountingResponse {
+    pub checked: u32,
+    pub mismatches: Vec<AccountingMismatch>,
+    /// Holder to continue from, `None` once the walk is complete
+    pub next: Option<String>,
+    /// Sum of the balances on this page. Summed over all pages it has to
+    /// equal `total_supply`.
+    pub page_balance: Uint128,
+    pub total_supply: Uint128,
+    /// NFTs on this page held by holders that skip NFTs. They are backed by
+    /// the holder's balance but only leave when that balance does.
+    pub skip_held_nfts: u128,
+}
+
+#[cw_serde]
+pub struct VerifyTokensResponse {
+    pub checked: u32,
+    pub mismatches: Vec<AccountingMismatch>,
+    /// Token id to continue from, `None` once the walk is complete
+    pub next: Option<String>,
+    /// Summed over all pages, `checked` has to equal this
+    pub live_nfts: Uint128,
+}
+
 #[cw_serde]
 #[derive(QueryResponses)]
 pub enum QueryMsg {
     #[returns(ConfigResponse)]
     Config {},
+    #[returns(Ownership<Addr>)]
+    Ownership {},
+    #[returns(Roles)]
+    Roles {},
     // cw20
     #[returns(BalanceResponse)]
     Balance { address: String },
@@ -140,16 +437,87 @@ pub enum QueryMsg {
     TokenInfo {},
     #[returns(MinterResponse)]
     Minter {},
+    #[returns(AllAccountsResponse)]
+    AllAccounts {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Number of accounts with a non-zero balance
+    #[returns(u64)]
+    HolderCount {},
+    #[returns(WhitelistedResponse)]
+    Whitelisted {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    #[returns(SupplyStatsResponse)]
+    SupplyStats {},
+    /// Wrapper mode only
+    #[returns(ReserveResponse)]
+    Reserve {},
+    #[returns(crate::state::TaxConfig)]
+    TaxConfig {},
+    #[returns(MarketingInfoResponse)]
+    MarketingInfo {},
+    /// Fails unless an embedded logo was uploaded
+    #[returns(DownloadLogoResponse)]
+    DownloadLogo {},
+    #[returns(bool)]
+    SkipNft { address: String },
+    #[returns(DualityInfoResponse)]
+    DualityInfo {},
+    #[returns(crate::state::ClaimConfig)]
+    ClaimConfig {},
+    #[returns(ClaimStatusResponse)]
+    ClaimStatus { address: String },
+    #[returns(crate::state::NftMetadata)]
+    NftMetadata {},
+    #[returns(Option<Addr>)]
+    Mirror {},
+    #[returns(Option<Addr>)]
+    GetApproved { token_id: String },
+    #[returns(bool)]
+    IsApprovedForAll { owner: String, operator: String },
+    #[returns(TokensResponse)]
+    AllTokens {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
     // cw721
+    #[returns(ContractInfoResponse)]
+    ContractInfo {},
     #[returns(OwnerOfResponse)]
     OwnerOf { token_id: String },
     #[returns(TokensResponse)]
     Tokens { owner: String },
+    /// The owner's NFTs pinned against burning
+    #[returns(TokensResponse)]
+    LockedTokens { owner: String },
     #[returns(NumTokensResponse)]
     NumTokens {},
-    #[returns(NftInfoResponse<Empty>)]
+    #[returns(NftInfoResponse<NftExtension>)]
     NftInfo { token_id: String },
+    /// Walks holders and cross-checks balances against NFT ownership
+    #[returns(VerifyAccountingResponse)]
+    VerifyAccounting {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Walks live token ids and checks each is listed by its owner
+    #[returns(VerifyTokensResponse)]
+    VerifyTokens {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
 }
 
 #[cw_serde]
-pub struct MigrateMsg {}
+pub struct MigrateMsg {
+    /// New total supply, in base units. The contract's own balance absorbs the
+    /// difference, so it cannot drop below what is already circulating.
+    pub total_supply: Option<Uint128>,
+    pub token_uri: Option<String>,
+    /// New fungible amount per NFT. Holders' NFTs are brought in line
+    /// afterwards with `Resync`.
+    pub units_per_nft: Option<Uint128>,
+}
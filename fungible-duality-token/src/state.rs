@@ -1,13 +1,41 @@
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{Addr, Uint128};
+use cosmwasm_std::{Addr, Binary, Coin, Timestamp, Uint128};
+use cw20::{Denom, Logo, MarketingInfoResponse};
 use cw_storage_plus::{Item, Map};
 
+/// Admin of contracts instantiated before cw-ownable, moved to the owner by
+/// `migrate`
 #[cw_serde]
 pub struct Config {
     pub admin: Addr,
 }
 pub const CONFIG: Item<Config> = Item::new("config");
 
+#[cw_serde]
+pub enum Role {
+    WhitelistManager,
+    AirdropOperator,
+}
+
+impl std::fmt::Display for Role {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Role::WhitelistManager => write!(f, "whitelist_manager"),
+            Role::AirdropOperator => write!(f, "airdrop_operator"),
+        }
+    }
+}
+
+/// Accounts the owner delegated a single duty to. The owner itself can
+/// always act in every role.
+#[cw_serde]
+#[derive(Default)]
+pub struct Roles {
+    pub whitelist_manager: Option<Addr>,
+    pub airdrop_operator: Option<Addr>,
+}
+pub const ROLES: Item<Roles> = Item::new("roles");
+
 #[cw_serde]
 pub struct Metadata {
     pub name: String,
@@ -15,6 +43,10 @@ pub struct Metadata {
     pub decimals: u8,
     pub total_supply: u128,
     pub token_uri: String,
+    /// Fungible base units backing one NFT. Missing on contracts instantiated
+    /// before it was configurable, `migrate` backfills it.
+    #[serde(default)]
+    pub units_per_nft: Uint128,
 }
 pub const METADATA: Item<Metadata> = Item::new("metadata");
 
@@ -22,6 +54,12 @@ pub const MINTED: Item<u128> = Item::new("minted");
 
 pub const BALANCE_OF: Map<Addr, Uint128> = Map::new("balance_of");
 
+/// Accounts holding a non-zero balance, the contract itself included
+pub const HOLDER_COUNT: Item<u64> = Item::new("holder_count");
+
+/// NFTs currently in existence, unlike `MINTED` which never goes down
+pub const LIVE_NFTS: Item<u128> = Item::new("live_nfts");
+
 pub const ALLOWANCE: Map<(Addr, Addr), Uint128> = Map::new("allowance");
 
 pub const GET_APPROVED: Map<u128, Addr> = Map::new("get_approved");
@@ -34,6 +72,82 @@ pub const OWNED: Map<Addr, Vec<u128>> = Map::new("owned");
 
 pub const OWNED_INDEX: Map<u128, u128> = Map::new("owned_index");
 
+/// NFTs pinned by their owner so fractional transfers never burn them.
+pub const LOCKED: Map<u128, bool> = Map::new("locked");
+
 pub const WHITELIST: Map<Addr, bool> = Map::new("whitelist");
 
+/// Asset backing the supply 1:1 in wrapper mode. Unset for contracts that
+/// issue their whole supply at instantiate.
+pub const UNDERLYING: Item<Denom> = Item::new("underlying");
+
+/// Fungible transfer tax, routed to `treasury`
+#[cw_serde]
+#[derive(Default)]
+pub struct TaxConfig {
+    pub bps: u16,
+    pub treasury: Option<Addr>,
+    /// Never taxed, on either side of a transfer. Whitelisted pools and the
+    /// contract itself are exempt without being listed.
+    pub exempt: Vec<Addr>,
+}
+pub const TAX_CONFIG: Item<TaxConfig> = Item::new("tax_config");
+
+pub const MARKETING_INFO: Item<MarketingInfoResponse> = Item::new("marketing_info");
+
+/// Uploaded logo. Only embedded logos are served by `DownloadLogo`.
+pub const LOGO: Item<Logo> = Item::new("logo");
+
+/// cw721 facade contract allowed to relay NFT calls for its callers
+pub const MIRROR: Item<Addr> = Item::new("mirror");
+
+/// Holder-chosen opt-out of NFT mirroring. Unset means the default applies:
+/// contracts skip NFTs, everyone else receives them.
+pub const SKIP_NFT: Map<Addr, bool> = Map::new("skip_nft");
+
 pub const LAST_MINT_SECONDS: Map<Addr, u64> = Map::new("last_mint_seconds");
+
+#[cw_serde]
+pub struct ClaimConfig {
+    /// Base units handed out per `Mint {}`
+    pub amount: Uint128,
+    /// Seconds an address waits between claims
+    pub cooldown: u64,
+    /// Lifetime amount a single address may claim
+    pub per_address_cap: Option<Uint128>,
+    /// Lifetime amount all addresses together may claim
+    pub global_cap: Option<Uint128>,
+    pub start_time: Option<Timestamp>,
+    pub end_time: Option<Timestamp>,
+    /// Native coin charged per claim and forwarded to `treasury`
+    pub fee: Option<Coin>,
+    pub treasury: Option<Addr>,
+    /// Hex-encoded root of the claimer allowlist
+    pub merkle_root: Option<String>,
+}
+pub const CLAIM_CONFIG: Item<ClaimConfig> = Item::new("claim_config");
+
+pub const CLAIMED: Map<Addr, Uint128> = Map::new("claimed");
+
+pub const TOTAL_CLAIMED: Item<Uint128> = Item::new("total_claimed");
+
+#[cw_serde]
+pub struct TraitDef {
+    pub trait_type: String,
+    pub values: Vec<String>,
+}
+
+#[cw_serde]
+pub struct NftMetadata {
+    /// Token URI template, `{id}` is replaced with the token id
+    pub base_uri: String,
+    /// Reported for every token until the collection is revealed
+    pub placeholder_uri: Option<String>,
+    pub traits: Vec<TraitDef>,
+    /// Frozen by `Reveal`; traits resolve only once it is set
+    pub reveal_seed: Option<Binary>,
+}
+pub const NFT_METADATA: Item<NftMetadata> = Item::new("nft_metadata");
+
+/// Block-derived randomness recorded for each token at mint
+pub const TOKEN_ENTROPY: Map<u128, Binary> = Map::new("token_entropy");